@@ -0,0 +1,57 @@
+-- Users, categories, incomes and expenses.
+-- Tables are created only if missing, so databases created by hand before migrations keep their data.
+
+CREATE TABLE IF NOT EXISTS ПОЛЬЗОВАТЕЛЬ (
+    USER_ID    INT          NOT NULL AUTO_INCREMENT,
+    NICKNAME   VARCHAR(255) NOT NULL,
+    PASSWORD   VARCHAR(255) NOT NULL,
+    FIRST_NAME VARCHAR(100) NULL,
+    LAST_NAME  VARCHAR(100) NULL,
+    PRIMARY KEY (USER_ID),
+    UNIQUE KEY UQ_USER_NICKNAME (NICKNAME)
+) DEFAULT CHARSET = utf8mb4;
+
+-- Categories without creator are shared between all users
+CREATE TABLE IF NOT EXISTS КАТЕГОРИЯ (
+    CATEGORY_ID      INT          NOT NULL AUTO_INCREMENT,
+    CATEGORY_CREATOR INT          NULL,
+    CATEGORY_NAME    VARCHAR(100) NOT NULL,
+    PRIMARY KEY (CATEGORY_ID),
+    KEY IX_CATEGORY_CREATOR (CATEGORY_CREATOR),
+    CONSTRAINT FK_CATEGORY_CREATOR FOREIGN KEY (CATEGORY_CREATOR) REFERENCES ПОЛЬЗОВАТЕЛЬ (USER_ID) ON DELETE CASCADE
+) DEFAULT CHARSET = utf8mb4;
+
+INSERT IGNORE INTO КАТЕГОРИЯ (CATEGORY_ID, CATEGORY_CREATOR, CATEGORY_NAME) VALUES
+    (1, NULL, 'Прочее'),
+    (2, NULL, 'Зарплата'),
+    (3, NULL, 'Продукты'),
+    (4, NULL, 'Жильё'),
+    (5, NULL, 'Транспорт');
+
+CREATE TABLE IF NOT EXISTS ДОХОД (
+    INCOMES_ID       INT          NOT NULL AUTO_INCREMENT,
+    INCOMES_CREATOR  INT          NOT NULL,
+    INCOMES_NAME     VARCHAR(255) NOT NULL,
+    INCOMES_PRICE    INT UNSIGNED NOT NULL,
+    INCOMES_CATEGORY INT          NOT NULL,
+    INCOMES_DATE     DATE         NOT NULL,
+    PRIMARY KEY (INCOMES_ID),
+    KEY IX_INCOMES_CREATOR_DATE (INCOMES_CREATOR, INCOMES_DATE),
+    KEY IX_INCOMES_CATEGORY (INCOMES_CATEGORY),
+    CONSTRAINT FK_INCOMES_CREATOR FOREIGN KEY (INCOMES_CREATOR) REFERENCES ПОЛЬЗОВАТЕЛЬ (USER_ID) ON DELETE CASCADE,
+    CONSTRAINT FK_INCOMES_CATEGORY FOREIGN KEY (INCOMES_CATEGORY) REFERENCES КАТЕГОРИЯ (CATEGORY_ID)
+) DEFAULT CHARSET = utf8mb4;
+
+CREATE TABLE IF NOT EXISTS РАСХОД (
+    EXPENSES_ID       INT          NOT NULL AUTO_INCREMENT,
+    EXPENSES_CREATOR  INT          NOT NULL,
+    EXPENSES_NAME     VARCHAR(255) NOT NULL,
+    EXPENSES_PRICE    INT UNSIGNED NOT NULL,
+    EXPENSES_CATEGORY INT          NOT NULL,
+    EXPENSES_DATE     DATE         NOT NULL,
+    PRIMARY KEY (EXPENSES_ID),
+    KEY IX_EXPENSES_CREATOR_DATE (EXPENSES_CREATOR, EXPENSES_DATE),
+    KEY IX_EXPENSES_CATEGORY (EXPENSES_CATEGORY),
+    CONSTRAINT FK_EXPENSES_CREATOR FOREIGN KEY (EXPENSES_CREATOR) REFERENCES ПОЛЬЗОВАТЕЛЬ (USER_ID) ON DELETE CASCADE,
+    CONSTRAINT FK_EXPENSES_CATEGORY FOREIGN KEY (EXPENSES_CATEGORY) REFERENCES КАТЕГОРИЯ (CATEGORY_ID)
+) DEFAULT CHARSET = utf8mb4;
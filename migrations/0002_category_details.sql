@@ -0,0 +1,17 @@
+-- Categories get a kind (income or expense), color, icon and optional parent for subcategories
+
+ALTER TABLE КАТЕГОРИЯ
+    ADD COLUMN CATEGORY_KIND   VARCHAR(10) NOT NULL DEFAULT 'EXPENSE',
+    ADD COLUMN CATEGORY_COLOR  CHAR(7)     NOT NULL DEFAULT '#BD93F9',
+    ADD COLUMN CATEGORY_ICON   VARCHAR(16) NOT NULL DEFAULT '',
+    ADD COLUMN CATEGORY_PARENT INT         NULL,
+    ADD KEY IX_CATEGORY_PARENT (CATEGORY_PARENT),
+    ADD CONSTRAINT FK_CATEGORY_PARENT FOREIGN KEY (CATEGORY_PARENT) REFERENCES КАТЕГОРИЯ (CATEGORY_ID) ON DELETE CASCADE;
+
+UPDATE КАТЕГОРИЯ SET CATEGORY_KIND = 'INCOME', CATEGORY_COLOR = '#50FA7B' WHERE CATEGORY_ID = 2;
+UPDATE КАТЕГОРИЯ SET CATEGORY_COLOR = '#FFB86C' WHERE CATEGORY_ID = 3;
+UPDATE КАТЕГОРИЯ SET CATEGORY_COLOR = '#8BE9FD' WHERE CATEGORY_ID = 4;
+UPDATE КАТЕГОРИЯ SET CATEGORY_COLOR = '#FF79C6' WHERE CATEGORY_ID = 5;
+
+INSERT IGNORE INTO КАТЕГОРИЯ (CATEGORY_ID, CATEGORY_CREATOR, CATEGORY_NAME, CATEGORY_KIND, CATEGORY_COLOR) VALUES
+    (6, NULL, 'Прочие доходы', 'INCOME', '#F1FA8C');
@@ -0,0 +1,4 @@
+-- Prices become exact decimals with kopecks/cents and may be negative (adjustments, refunds)
+
+ALTER TABLE ДОХОД MODIFY COLUMN INCOMES_PRICE DECIMAL(15, 2) NOT NULL;
+ALTER TABLE РАСХОД MODIFY COLUMN EXPENSES_PRICE DECIMAL(15, 2) NOT NULL;
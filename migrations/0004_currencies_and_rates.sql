@@ -0,0 +1,17 @@
+-- Every income and expense keeps its own currency, rates are entered by users to convert totals
+
+ALTER TABLE ДОХОД ADD COLUMN INCOMES_CURRENCY CHAR(3) NOT NULL DEFAULT 'RUB' AFTER INCOMES_PRICE;
+ALTER TABLE РАСХОД ADD COLUMN EXPENSES_CURRENCY CHAR(3) NOT NULL DEFAULT 'RUB' AFTER EXPENSES_PRICE;
+
+-- One unit of RATE_FROM costs RATE_VALUE units of RATE_TO starting from RATE_DATE
+CREATE TABLE IF NOT EXISTS КУРС (
+    RATE_ID      INT            NOT NULL AUTO_INCREMENT,
+    RATE_CREATOR INT            NOT NULL,
+    RATE_FROM    CHAR(3)        NOT NULL,
+    RATE_TO      CHAR(3)        NOT NULL,
+    RATE_VALUE   DECIMAL(18, 6) NOT NULL,
+    RATE_DATE    DATE           NOT NULL,
+    PRIMARY KEY (RATE_ID),
+    UNIQUE KEY UQ_RATE (RATE_CREATOR, RATE_FROM, RATE_TO, RATE_DATE),
+    CONSTRAINT FK_RATE_CREATOR FOREIGN KEY (RATE_CREATOR) REFERENCES ПОЛЬЗОВАТЕЛЬ (USER_ID) ON DELETE CASCADE
+) DEFAULT CHARSET = utf8mb4;
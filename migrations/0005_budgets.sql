@@ -0,0 +1,12 @@
+-- Monthly spending limit of a category, the same for every month until changed
+CREATE TABLE IF NOT EXISTS БЮДЖЕТ (
+    BUDGET_ID       INT            NOT NULL AUTO_INCREMENT,
+    BUDGET_CREATOR  INT            NOT NULL,
+    BUDGET_CATEGORY INT            NOT NULL,
+    BUDGET_LIMIT    DECIMAL(15, 2) NOT NULL,
+    BUDGET_CURRENCY CHAR(3)        NOT NULL DEFAULT 'RUB',
+    PRIMARY KEY (BUDGET_ID),
+    UNIQUE KEY UQ_BUDGET (BUDGET_CREATOR, BUDGET_CATEGORY),
+    CONSTRAINT FK_BUDGET_CREATOR FOREIGN KEY (BUDGET_CREATOR) REFERENCES ПОЛЬЗОВАТЕЛЬ (USER_ID) ON DELETE CASCADE,
+    CONSTRAINT FK_BUDGET_CATEGORY FOREIGN KEY (BUDGET_CATEGORY) REFERENCES КАТЕГОРИЯ (CATEGORY_ID) ON DELETE CASCADE
+) DEFAULT CHARSET = utf8mb4;
@@ -0,0 +1,29 @@
+-- Rules for incomes and expenses which repeat (salary, rent, subscriptions).
+-- TEMPLATE_GENERATED_UNTIL is the last day for which occurrences were already created
+CREATE TABLE IF NOT EXISTS ШАБЛОН (
+    TEMPLATE_ID              INT            NOT NULL AUTO_INCREMENT,
+    TEMPLATE_CREATOR         INT            NOT NULL,
+    TEMPLATE_KIND            VARCHAR(10)    NOT NULL,
+    TEMPLATE_NAME            VARCHAR(255)   NOT NULL,
+    TEMPLATE_PRICE           DECIMAL(15, 2) NOT NULL,
+    TEMPLATE_CURRENCY        CHAR(3)        NOT NULL DEFAULT 'RUB',
+    TEMPLATE_CATEGORY        INT            NOT NULL,
+    TEMPLATE_PERIOD          VARCHAR(10)    NOT NULL,
+    TEMPLATE_START           DATE           NOT NULL,
+    TEMPLATE_END             DATE           NULL,
+    TEMPLATE_GENERATED_UNTIL DATE           NULL,
+    PRIMARY KEY (TEMPLATE_ID),
+    CONSTRAINT FK_TEMPLATE_CREATOR FOREIGN KEY (TEMPLATE_CREATOR) REFERENCES ПОЛЬЗОВАТЕЛЬ (USER_ID) ON DELETE CASCADE,
+    CONSTRAINT FK_TEMPLATE_CATEGORY FOREIGN KEY (TEMPLATE_CATEGORY) REFERENCES КАТЕГОРИЯ (CATEGORY_ID)
+) DEFAULT CHARSET = utf8mb4;
+
+-- Created rows remember their template, so one occurrence is never inserted twice
+ALTER TABLE ДОХОД
+    ADD COLUMN INCOMES_TEMPLATE INT NULL,
+    ADD CONSTRAINT FK_INCOMES_TEMPLATE FOREIGN KEY (INCOMES_TEMPLATE) REFERENCES ШАБЛОН (TEMPLATE_ID) ON DELETE SET NULL,
+    ADD UNIQUE KEY UQ_INCOMES_OCCURRENCE (INCOMES_TEMPLATE, INCOMES_DATE);
+
+ALTER TABLE РАСХОД
+    ADD COLUMN EXPENSES_TEMPLATE INT NULL,
+    ADD CONSTRAINT FK_EXPENSES_TEMPLATE FOREIGN KEY (EXPENSES_TEMPLATE) REFERENCES ШАБЛОН (TEMPLATE_ID) ON DELETE SET NULL,
+    ADD UNIQUE KEY UQ_EXPENSES_OCCURRENCE (EXPENSES_TEMPLATE, EXPENSES_DATE);
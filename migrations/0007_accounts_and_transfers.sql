@@ -0,0 +1,39 @@
+-- Places where money lives (cash, card, savings) with the balance they had when added
+CREATE TABLE IF NOT EXISTS СЧЕТ (
+    ACCOUNT_ID       INT            NOT NULL AUTO_INCREMENT,
+    ACCOUNT_CREATOR  INT            NOT NULL,
+    ACCOUNT_NAME     VARCHAR(255)   NOT NULL,
+    ACCOUNT_OPENING  DECIMAL(15, 2) NOT NULL DEFAULT 0,
+    ACCOUNT_CURRENCY CHAR(3)        NOT NULL DEFAULT 'RUB',
+    PRIMARY KEY (ACCOUNT_ID),
+    UNIQUE KEY UQ_ACCOUNT_NAME (ACCOUNT_CREATOR, ACCOUNT_NAME),
+    CONSTRAINT FK_ACCOUNT_CREATOR FOREIGN KEY (ACCOUNT_CREATOR) REFERENCES ПОЛЬЗОВАТЕЛЬ (USER_ID) ON DELETE CASCADE
+) DEFAULT CHARSET = utf8mb4;
+
+-- Incomes and expenses entered before accounts existed have no account
+ALTER TABLE ДОХОД
+    ADD COLUMN INCOMES_ACCOUNT INT NULL AFTER INCOMES_CATEGORY,
+    ADD CONSTRAINT FK_INCOMES_ACCOUNT FOREIGN KEY (INCOMES_ACCOUNT) REFERENCES СЧЕТ (ACCOUNT_ID) ON DELETE SET NULL;
+
+ALTER TABLE РАСХОД
+    ADD COLUMN EXPENSES_ACCOUNT INT NULL AFTER EXPENSES_CATEGORY,
+    ADD CONSTRAINT FK_EXPENSES_ACCOUNT FOREIGN KEY (EXPENSES_ACCOUNT) REFERENCES СЧЕТ (ACCOUNT_ID) ON DELETE SET NULL;
+
+-- Moving money between own accounts, it is neither income nor expense.
+-- Amount received may differ from the sent one when currencies of accounts differ.
+-- Transfers are deleted together with any of their accounts
+CREATE TABLE IF NOT EXISTS ПЕРЕВОД (
+    TRANSFER_ID                INT            NOT NULL AUTO_INCREMENT,
+    TRANSFER_CREATOR           INT            NOT NULL,
+    TRANSFER_FROM              INT            NOT NULL,
+    TRANSFER_TO                INT            NOT NULL,
+    TRANSFER_SENT              DECIMAL(15, 2) NOT NULL,
+    TRANSFER_SENT_CURRENCY     CHAR(3)        NOT NULL,
+    TRANSFER_RECEIVED          DECIMAL(15, 2) NOT NULL,
+    TRANSFER_RECEIVED_CURRENCY CHAR(3)        NOT NULL,
+    TRANSFER_DATE              DATE           NOT NULL,
+    PRIMARY KEY (TRANSFER_ID),
+    CONSTRAINT FK_TRANSFER_CREATOR FOREIGN KEY (TRANSFER_CREATOR) REFERENCES ПОЛЬЗОВАТЕЛЬ (USER_ID) ON DELETE CASCADE,
+    CONSTRAINT FK_TRANSFER_FROM FOREIGN KEY (TRANSFER_FROM) REFERENCES СЧЕТ (ACCOUNT_ID) ON DELETE CASCADE,
+    CONSTRAINT FK_TRANSFER_TO FOREIGN KEY (TRANSFER_TO) REFERENCES СЧЕТ (ACCOUNT_ID) ON DELETE CASCADE
+) DEFAULT CHARSET = utf8mb4;
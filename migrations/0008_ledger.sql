@@ -0,0 +1,38 @@
+-- Chart of accounts. Codes are derived from what the account stands for:
+-- 1.<wallet id> assets, 3.x equity, 4.<category id> revenue, 5.<category id> expenses
+CREATE TABLE IF NOT EXISTS ПЛАН_СЧЕТОВ (
+    LEDGER_ID      INT          NOT NULL AUTO_INCREMENT,
+    LEDGER_CREATOR INT          NOT NULL,
+    LEDGER_CODE    VARCHAR(20)  NOT NULL,
+    LEDGER_NAME    VARCHAR(255) NOT NULL,
+    LEDGER_KIND    VARCHAR(10)  NOT NULL,
+    PRIMARY KEY (LEDGER_ID),
+    UNIQUE KEY UQ_LEDGER_CODE (LEDGER_CREATOR, LEDGER_CODE),
+    CONSTRAINT FK_LEDGER_CREATOR FOREIGN KEY (LEDGER_CREATOR) REFERENCES ПОЛЬЗОВАТЕЛЬ (USER_ID) ON DELETE CASCADE
+) DEFAULT CHARSET = utf8mb4;
+
+-- One balanced entry for every income, expense, transfer and opening balance of a wallet
+CREATE TABLE IF NOT EXISTS ЖУРНАЛ (
+    ENTRY_ID        INT          NOT NULL AUTO_INCREMENT,
+    ENTRY_CREATOR   INT          NOT NULL,
+    ENTRY_DATE      DATE         NOT NULL,
+    ENTRY_MEMO      VARCHAR(255) NOT NULL,
+    ENTRY_SOURCE    VARCHAR(10)  NOT NULL,
+    ENTRY_SOURCE_ID INT          NOT NULL,
+    PRIMARY KEY (ENTRY_ID),
+    UNIQUE KEY UQ_ENTRY_SOURCE (ENTRY_CREATOR, ENTRY_SOURCE, ENTRY_SOURCE_ID),
+    CONSTRAINT FK_ENTRY_CREATOR FOREIGN KEY (ENTRY_CREATOR) REFERENCES ПОЛЬЗОВАТЕЛЬ (USER_ID) ON DELETE CASCADE
+) DEFAULT CHARSET = utf8mb4;
+
+-- Exactly one of debit and credit is not zero
+CREATE TABLE IF NOT EXISTS ПРОВОДКА (
+    POSTING_ID       INT            NOT NULL AUTO_INCREMENT,
+    POSTING_ENTRY    INT            NOT NULL,
+    POSTING_LEDGER   INT            NOT NULL,
+    POSTING_DEBIT    DECIMAL(15, 2) NOT NULL DEFAULT 0,
+    POSTING_CREDIT   DECIMAL(15, 2) NOT NULL DEFAULT 0,
+    POSTING_CURRENCY CHAR(3)        NOT NULL,
+    PRIMARY KEY (POSTING_ID),
+    CONSTRAINT FK_POSTING_ENTRY FOREIGN KEY (POSTING_ENTRY) REFERENCES ЖУРНАЛ (ENTRY_ID) ON DELETE CASCADE,
+    CONSTRAINT FK_POSTING_LEDGER FOREIGN KEY (POSTING_LEDGER) REFERENCES ПЛАН_СЧЕТОВ (LEDGER_ID)
+) DEFAULT CHARSET = utf8mb4;
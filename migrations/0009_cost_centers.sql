@@ -0,0 +1,25 @@
+-- Projects and departments spending is attributed to
+CREATE TABLE IF NOT EXISTS ЦЕНТР_ЗАТРАТ (
+    CENTER_ID      INT          NOT NULL AUTO_INCREMENT,
+    CENTER_CREATOR INT          NOT NULL,
+    CENTER_NAME    VARCHAR(255) NOT NULL,
+    PRIMARY KEY (CENTER_ID),
+    UNIQUE KEY UQ_CENTER_NAME (CENTER_CREATOR, CENTER_NAME),
+    CONSTRAINT FK_CENTER_CREATOR FOREIGN KEY (CENTER_CREATOR) REFERENCES ПОЛЬЗОВАТЕЛЬ (USER_ID) ON DELETE CASCADE
+) DEFAULT CHARSET = utf8mb4;
+
+-- Part of an expense attributed to a cost center: either a percentage of the expense
+-- or a fixed amount in its currency. The rest of the expense stays unallocated
+CREATE TABLE IF NOT EXISTS РАЗНЕСЕНИЕ (
+    SPLIT_ID       INT            NOT NULL AUTO_INCREMENT,
+    SPLIT_EXPENSE  INT            NOT NULL,
+    SPLIT_CENTER   INT            NOT NULL,
+    SPLIT_PERCENT  DECIMAL(5, 2)  NULL,
+    SPLIT_AMOUNT   DECIMAL(15, 2) NULL,
+    SPLIT_CURRENCY CHAR(3)        NULL,
+    PRIMARY KEY (SPLIT_ID),
+    UNIQUE KEY UQ_SPLIT (SPLIT_EXPENSE, SPLIT_CENTER),
+    CONSTRAINT FK_SPLIT_EXPENSE FOREIGN KEY (SPLIT_EXPENSE) REFERENCES РАСХОД (EXPENSES_ID) ON DELETE CASCADE,
+    CONSTRAINT FK_SPLIT_CENTER FOREIGN KEY (SPLIT_CENTER) REFERENCES ЦЕНТР_ЗАТРАТ (CENTER_ID) ON DELETE CASCADE,
+    CONSTRAINT CHK_SPLIT_SHARE CHECK ((SPLIT_PERCENT IS NULL) <> (SPLIT_AMOUNT IS NULL))
+) DEFAULT CHARSET = utf8mb4;
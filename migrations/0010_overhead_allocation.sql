@@ -0,0 +1,40 @@
+-- Overhead category (together with its subcategories) distributed across cost centers by a driver
+CREATE TABLE IF NOT EXISTS ПРАВИЛО_РАСПРЕДЕЛЕНИЯ (
+    RULE_ID       INT         NOT NULL AUTO_INCREMENT,
+    RULE_CREATOR  INT         NOT NULL,
+    RULE_CATEGORY INT         NOT NULL,
+    RULE_DRIVER   VARCHAR(16) NOT NULL,
+    PRIMARY KEY (RULE_ID),
+    UNIQUE KEY UQ_RULE (RULE_CREATOR, RULE_CATEGORY),
+    CONSTRAINT FK_RULE_CREATOR FOREIGN KEY (RULE_CREATOR) REFERENCES ПОЛЬЗОВАТЕЛЬ (USER_ID) ON DELETE CASCADE,
+    CONSTRAINT FK_RULE_CATEGORY FOREIGN KEY (RULE_CATEGORY) REFERENCES КАТЕГОРИЯ (CATEGORY_ID) ON DELETE CASCADE
+) DEFAULT CHARSET = utf8mb4;
+
+-- Headcount, floor area or custom weight of a cost center.
+-- Direct cost share is calculated from expenses and isn't stored
+CREATE TABLE IF NOT EXISTS ДРАЙВЕР (
+    DRIVER_ID     INT            NOT NULL AUTO_INCREMENT,
+    DRIVER_CENTER INT            NOT NULL,
+    DRIVER_KIND   VARCHAR(16)    NOT NULL,
+    DRIVER_VALUE  DECIMAL(15, 2) NOT NULL,
+    PRIMARY KEY (DRIVER_ID),
+    UNIQUE KEY UQ_DRIVER (DRIVER_CENTER, DRIVER_KIND),
+    CONSTRAINT FK_DRIVER_CENTER FOREIGN KEY (DRIVER_CENTER) REFERENCES ЦЕНТР_ЗАТРАТ (CENTER_ID) ON DELETE CASCADE
+) DEFAULT CHARSET = utf8mb4;
+
+-- Result of the last allocation run of a month (ALLOCATION_PERIOD is its first day).
+-- Running allocation for the month again replaces its rows
+CREATE TABLE IF NOT EXISTS РАСПРЕДЕЛЕНИЕ_НАКЛАДНЫХ (
+    ALLOCATION_ID       INT            NOT NULL AUTO_INCREMENT,
+    ALLOCATION_CREATOR  INT            NOT NULL,
+    ALLOCATION_PERIOD   DATE           NOT NULL,
+    ALLOCATION_CATEGORY INT            NOT NULL,
+    ALLOCATION_CENTER   INT            NOT NULL,
+    ALLOCATION_AMOUNT   DECIMAL(15, 2) NOT NULL,
+    ALLOCATION_CURRENCY CHAR(3)        NOT NULL,
+    PRIMARY KEY (ALLOCATION_ID),
+    KEY IX_ALLOCATION_PERIOD (ALLOCATION_CREATOR, ALLOCATION_PERIOD),
+    CONSTRAINT FK_ALLOCATION_CREATOR FOREIGN KEY (ALLOCATION_CREATOR) REFERENCES ПОЛЬЗОВАТЕЛЬ (USER_ID) ON DELETE CASCADE,
+    CONSTRAINT FK_ALLOCATION_CATEGORY FOREIGN KEY (ALLOCATION_CATEGORY) REFERENCES КАТЕГОРИЯ (CATEGORY_ID) ON DELETE CASCADE,
+    CONSTRAINT FK_ALLOCATION_CENTER FOREIGN KEY (ALLOCATION_CENTER) REFERENCES ЦЕНТР_ЗАТРАТ (CENTER_ID) ON DELETE CASCADE
+) DEFAULT CHARSET = utf8mb4;
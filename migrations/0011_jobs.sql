@@ -0,0 +1,24 @@
+-- Orders and projects which incomes and expenses are linked to, to see whether each of them made money.
+-- Job of a cost center gets a part of the overhead allocated to that center
+CREATE TABLE IF NOT EXISTS ЗАКАЗ (
+    JOB_ID      INT          NOT NULL AUTO_INCREMENT,
+    JOB_CREATOR INT          NOT NULL,
+    JOB_NAME    VARCHAR(255) NOT NULL,
+    JOB_CLIENT  VARCHAR(255) NOT NULL DEFAULT '',
+    JOB_START   DATE         NOT NULL,
+    JOB_END     DATE         NULL,
+    JOB_STATUS  VARCHAR(16)  NOT NULL DEFAULT 'ACTIVE',
+    JOB_CENTER  INT          NULL,
+    PRIMARY KEY (JOB_ID),
+    UNIQUE KEY UQ_JOB_NAME (JOB_CREATOR, JOB_NAME),
+    CONSTRAINT FK_JOB_CREATOR FOREIGN KEY (JOB_CREATOR) REFERENCES ПОЛЬЗОВАТЕЛЬ (USER_ID) ON DELETE CASCADE,
+    CONSTRAINT FK_JOB_CENTER FOREIGN KEY (JOB_CENTER) REFERENCES ЦЕНТР_ЗАТРАТ (CENTER_ID) ON DELETE SET NULL
+) DEFAULT CHARSET = utf8mb4;
+
+ALTER TABLE ДОХОД
+    ADD COLUMN INCOMES_JOB INT NULL AFTER INCOMES_ACCOUNT,
+    ADD CONSTRAINT FK_INCOMES_JOB FOREIGN KEY (INCOMES_JOB) REFERENCES ЗАКАЗ (JOB_ID) ON DELETE SET NULL;
+
+ALTER TABLE РАСХОД
+    ADD COLUMN EXPENSES_JOB INT NULL AFTER EXPENSES_ACCOUNT,
+    ADD CONSTRAINT FK_EXPENSES_JOB FOREIGN KEY (EXPENSES_JOB) REFERENCES ЗАКАЗ (JOB_ID) ON DELETE SET NULL;
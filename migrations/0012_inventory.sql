@@ -0,0 +1,38 @@
+-- Goods kept in stock. Cost of goods sold and ending inventory of an item are valued
+-- by its method (FIFO, LIFO or weighted average), all lots of an item are in its currency
+CREATE TABLE IF NOT EXISTS ТОВАР (
+    ITEM_ID       INT          NOT NULL AUTO_INCREMENT,
+    ITEM_CREATOR  INT          NOT NULL,
+    ITEM_NAME     VARCHAR(255) NOT NULL,
+    ITEM_UNIT     VARCHAR(16)  NOT NULL,
+    ITEM_METHOD   VARCHAR(16)  NOT NULL DEFAULT 'FIFO',
+    ITEM_CURRENCY CHAR(3)      NOT NULL,
+    PRIMARY KEY (ITEM_ID),
+    UNIQUE KEY UQ_ITEM_NAME (ITEM_CREATOR, ITEM_NAME),
+    CONSTRAINT FK_ITEM_CREATOR FOREIGN KEY (ITEM_CREATOR) REFERENCES ПОЛЬЗОВАТЕЛЬ (USER_ID) ON DELETE CASCADE
+) DEFAULT CHARSET = utf8mb4;
+
+-- Purchase of an item: quantity bought on the date at the unit cost
+CREATE TABLE IF NOT EXISTS ПАРТИЯ (
+    LOT_ID        INT            NOT NULL AUTO_INCREMENT,
+    LOT_ITEM      INT            NOT NULL,
+    LOT_DATE      DATE           NOT NULL,
+    LOT_QUANTITY  DECIMAL(15, 3) NOT NULL,
+    LOT_UNIT_COST DECIMAL(15, 2) NOT NULL,
+    PRIMARY KEY (LOT_ID),
+    KEY IX_LOT_DATE (LOT_ITEM, LOT_DATE),
+    CONSTRAINT FK_LOT_ITEM FOREIGN KEY (LOT_ITEM) REFERENCES ТОВАР (ITEM_ID) ON DELETE CASCADE,
+    CONSTRAINT CHK_LOT_QUANTITY CHECK (LOT_QUANTITY > 0 AND LOT_UNIT_COST >= 0)
+) DEFAULT CHARSET = utf8mb4;
+
+-- Sale or other issue of an item from stock, its cost depends on the method of the item
+CREATE TABLE IF NOT EXISTS СПИСАНИЕ (
+    ISSUE_ID       INT            NOT NULL AUTO_INCREMENT,
+    ISSUE_ITEM     INT            NOT NULL,
+    ISSUE_DATE     DATE           NOT NULL,
+    ISSUE_QUANTITY DECIMAL(15, 3) NOT NULL,
+    PRIMARY KEY (ISSUE_ID),
+    KEY IX_ISSUE_DATE (ISSUE_ITEM, ISSUE_DATE),
+    CONSTRAINT FK_ISSUE_ITEM FOREIGN KEY (ISSUE_ITEM) REFERENCES ТОВАР (ITEM_ID) ON DELETE CASCADE,
+    CONSTRAINT CHK_ISSUE_QUANTITY CHECK (ISSUE_QUANTITY > 0)
+) DEFAULT CHARSET = utf8mb4;
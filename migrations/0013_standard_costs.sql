@@ -0,0 +1,18 @@
+-- Standard quantity and unit price of an inventory item or an expense category (together with
+-- its subcategories) for every month. Exactly one of STANDARD_ITEM and STANDARD_CATEGORY is set,
+-- it isn't a CHECK because MySQL doesn't allow them on columns of cascading foreign keys
+CREATE TABLE IF NOT EXISTS НОРМАТИВ (
+    STANDARD_ID       INT            NOT NULL AUTO_INCREMENT,
+    STANDARD_CREATOR  INT            NOT NULL,
+    STANDARD_ITEM     INT            NULL,
+    STANDARD_CATEGORY INT            NULL,
+    STANDARD_QUANTITY DECIMAL(15, 3) NOT NULL,
+    STANDARD_PRICE    DECIMAL(15, 2) NOT NULL,
+    STANDARD_CURRENCY CHAR(3)        NOT NULL,
+    PRIMARY KEY (STANDARD_ID),
+    UNIQUE KEY UQ_STANDARD_ITEM (STANDARD_CREATOR, STANDARD_ITEM),
+    UNIQUE KEY UQ_STANDARD_CATEGORY (STANDARD_CREATOR, STANDARD_CATEGORY),
+    CONSTRAINT FK_STANDARD_CREATOR FOREIGN KEY (STANDARD_CREATOR) REFERENCES ПОЛЬЗОВАТЕЛЬ (USER_ID) ON DELETE CASCADE,
+    CONSTRAINT FK_STANDARD_ITEM FOREIGN KEY (STANDARD_ITEM) REFERENCES ТОВАР (ITEM_ID) ON DELETE CASCADE,
+    CONSTRAINT FK_STANDARD_CATEGORY FOREIGN KEY (STANDARD_CATEGORY) REFERENCES КАТЕГОРИЯ (CATEGORY_ID) ON DELETE CASCADE
+) DEFAULT CHARSET = utf8mb4;
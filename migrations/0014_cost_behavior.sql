@@ -0,0 +1,10 @@
+-- Whether spending in an expense category changes with the volume of sales (variable) or not (fixed).
+-- Kept per user, because categories may be shared. Subcategories without a row follow their parent
+CREATE TABLE IF NOT EXISTS ПОВЕДЕНИЕ_ЗАТРАТ (
+    BEHAVIOR_CREATOR  INT         NOT NULL,
+    BEHAVIOR_CATEGORY INT         NOT NULL,
+    BEHAVIOR_KIND     VARCHAR(16) NOT NULL,
+    PRIMARY KEY (BEHAVIOR_CREATOR, BEHAVIOR_CATEGORY),
+    CONSTRAINT FK_BEHAVIOR_CREATOR FOREIGN KEY (BEHAVIOR_CREATOR) REFERENCES ПОЛЬЗОВАТЕЛЬ (USER_ID) ON DELETE CASCADE,
+    CONSTRAINT FK_BEHAVIOR_CATEGORY FOREIGN KEY (BEHAVIOR_CATEGORY) REFERENCES КАТЕГОРИЯ (CATEGORY_ID) ON DELETE CASCADE
+) DEFAULT CHARSET = utf8mb4;
@@ -0,0 +1,36 @@
+-- Large purchases which are used for years. Their cost minus the salvage value is spread over the
+-- useful life (in months) as monthly depreciation expenses of ASSET_CATEGORY
+CREATE TABLE IF NOT EXISTS ОСНОВНОЕ_СРЕДСТВО (
+    ASSET_ID       INT            NOT NULL AUTO_INCREMENT,
+    ASSET_CREATOR  INT            NOT NULL,
+    ASSET_NAME     VARCHAR(255)   NOT NULL,
+    ASSET_COST     DECIMAL(15, 2) NOT NULL,
+    ASSET_SALVAGE  DECIMAL(15, 2) NOT NULL DEFAULT 0,
+    ASSET_CURRENCY CHAR(3)        NOT NULL,
+    ASSET_ACQUIRED DATE           NOT NULL,
+    ASSET_LIFE     INT UNSIGNED   NOT NULL,
+    ASSET_METHOD   VARCHAR(16)    NOT NULL DEFAULT 'STRAIGHT_LINE',
+    ASSET_UNITS    DECIMAL(15, 3) NULL,
+    ASSET_CATEGORY INT            NOT NULL,
+    PRIMARY KEY (ASSET_ID),
+    UNIQUE KEY UQ_ASSET_NAME (ASSET_CREATOR, ASSET_NAME),
+    CONSTRAINT FK_ASSET_CREATOR FOREIGN KEY (ASSET_CREATOR) REFERENCES ПОЛЬЗОВАТЕЛЬ (USER_ID) ON DELETE CASCADE,
+    CONSTRAINT FK_ASSET_CATEGORY FOREIGN KEY (ASSET_CATEGORY) REFERENCES КАТЕГОРИЯ (CATEGORY_ID),
+    CONSTRAINT CHK_ASSET_VALUES CHECK (ASSET_SALVAGE >= 0 AND ASSET_COST > ASSET_SALVAGE AND ASSET_LIFE > 0)
+) DEFAULT CHARSET = utf8mb4;
+
+-- Units produced by an asset depreciated by units-of-production, month is stored as its first day
+CREATE TABLE IF NOT EXISTS ВЫРАБОТКА (
+    USAGE_ASSET INT            NOT NULL,
+    USAGE_MONTH DATE           NOT NULL,
+    USAGE_UNITS DECIMAL(15, 3) NOT NULL,
+    PRIMARY KEY (USAGE_ASSET, USAGE_MONTH),
+    CONSTRAINT FK_USAGE_ASSET FOREIGN KEY (USAGE_ASSET) REFERENCES ОСНОВНОЕ_СРЕДСТВО (ASSET_ID) ON DELETE CASCADE
+) DEFAULT CHARSET = utf8mb4;
+
+-- Depreciation expense remembers its asset, so one month is never posted twice.
+-- Expenses of a removed asset are removed with it
+ALTER TABLE РАСХОД
+    ADD COLUMN EXPENSES_ASSET INT NULL AFTER EXPENSES_JOB,
+    ADD CONSTRAINT FK_EXPENSES_ASSET FOREIGN KEY (EXPENSES_ASSET) REFERENCES ОСНОВНОЕ_СРЕДСТВО (ASSET_ID) ON DELETE CASCADE,
+    ADD UNIQUE KEY UQ_EXPENSES_DEPRECIATION (EXPENSES_ASSET, EXPENSES_DATE);
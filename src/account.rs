@@ -0,0 +1,241 @@
+use std::fmt::{Display, Formatter};
+use sqlx::{FromRow, Row};
+use sqlx::mysql::MySqlRow;
+use time::Date;
+use crate::exchange::{ConversionError, ExchangeRate, total_in};
+use crate::expense::Expense;
+use crate::income::Income;
+use crate::money::{Currency, Money};
+
+#[derive(Clone, Debug, PartialEq, FromRow)]
+pub struct Account {
+    account_id: i32,
+    account_name: String,
+    //balance before any income, expense or transfer, read from `price` and `currency` columns
+    #[sqlx(flatten)]
+    account_opening: Money
+}
+
+impl Account {
+    pub fn new(account_id: i32, account_name: String, account_opening: Money) -> Self {
+        Self {
+            account_id,
+            account_name,
+            account_opening
+        }
+    }
+
+    pub fn get_id(&self) -> i32 {
+        self.account_id
+    }
+
+    pub fn get_name(&self) -> &str {
+        &self.account_name
+    }
+
+    pub fn get_opening(&self) -> Money {
+        self.account_opening
+    }
+
+    pub fn get_currency(&self) -> Currency {
+        self.account_opening.currency()
+    }
+}
+
+//Shown in pick lists
+impl Display for Account {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} ({})", self.account_name, self.get_currency())
+    }
+}
+
+pub struct AccountDraft {
+    pub name: String,
+    pub opening: Money
+}
+
+#[derive(Clone, Debug)]
+pub struct Transfer {
+    transfer_id: i32,
+    transfer_from: i32,
+    transfer_to: i32,
+    //in the currency of the account it was sent from
+    transfer_sent: Money,
+    //in the currency of the account it was received to
+    transfer_received: Money,
+    transfer_date: Date
+}
+
+impl Transfer {
+    pub fn new(transfer_id: i32, draft: TransferDraft) -> Self {
+        Self {
+            transfer_id,
+            transfer_from: draft.from,
+            transfer_to: draft.to,
+            transfer_sent: draft.sent,
+            transfer_received: draft.received,
+            transfer_date: draft.date
+        }
+    }
+
+    pub fn get_id(&self) -> i32 {
+        self.transfer_id
+    }
+
+    pub fn get_from(&self) -> i32 {
+        self.transfer_from
+    }
+
+    pub fn get_to(&self) -> i32 {
+        self.transfer_to
+    }
+
+    pub fn get_sent(&self) -> Money {
+        self.transfer_sent
+    }
+
+    pub fn get_received(&self) -> Money {
+        self.transfer_received
+    }
+
+    pub fn get_date(&self) -> Date {
+        self.transfer_date
+    }
+}
+
+//Queries alias amounts to `sent`/`sent_currency` and `received`/`received_currency`
+impl<'r> FromRow<'r, MySqlRow> for Transfer {
+    fn from_row(row: &'r MySqlRow) -> Result<Self, sqlx::Error> {
+        Ok(Transfer {
+            transfer_id: row.try_get("transfer_id")?,
+            transfer_from: row.try_get("transfer_from")?,
+            transfer_to: row.try_get("transfer_to")?,
+            transfer_sent: Money::from_columns(row, "sent", "sent_currency")?,
+            transfer_received: Money::from_columns(row, "received", "received_currency")?,
+            transfer_date: row.try_get("transfer_date")?
+        })
+    }
+}
+
+pub struct TransferDraft {
+    pub from: i32,
+    pub to: i32,
+    pub sent: Money,
+    pub received: Money,
+    pub date: Date
+}
+
+//Incomes and expenses in another currency than the account are converted with the rates of their dates
+pub fn balance(account: &Account, incomes: &[Income], expenses: &[Expense], transfers: &[Transfer], rates: &[ExchangeRate]) -> Result<Money, ConversionError> {
+    let currency = account.get_currency();
+    let id = Some(account.account_id);
+
+    let earned = total_in(rates, incomes.iter().filter(|income| income.get_account() == id).map(|income| (income.get_price(), income.get_date())), currency)?;
+    let spent = total_in(rates, expenses.iter().filter(|expense| expense.get_account() == id).map(|expense| (expense.get_price(), expense.get_date())), currency)?;
+    let received = total_in(rates, transfers.iter().filter(|transfer| transfer.transfer_to == account.account_id).map(|transfer| (transfer.transfer_received, transfer.transfer_date)), currency)?;
+    let sent = total_in(rates, transfers.iter().filter(|transfer| transfer.transfer_from == account.account_id).map(|transfer| (transfer.transfer_sent, transfer.transfer_date)), currency)?;
+
+    Ok(account.account_opening
+        .checked_add(earned)?
+        .checked_add(received)?
+        .checked_sub(spent)?
+        .checked_sub(sent)?)
+}
+
+//Date of the earliest income, expense or transfer of the account, the opening balance is dated with it
+pub fn first_activity(account: &Account, incomes: &[Income], expenses: &[Expense], transfers: &[Transfer]) -> Option<Date> {
+    let id = Some(account.account_id);
+
+    incomes
+        .iter()
+        .filter(|income| income.get_account() == id)
+        .map(|income| income.get_date())
+        .chain(expenses.iter().filter(|expense| expense.get_account() == id).map(|expense| expense.get_date()))
+        .chain(transfers
+            .iter()
+            .filter(|transfer| transfer.transfer_from == account.account_id || transfer.transfer_to == account.account_id)
+            .map(|transfer| transfer.transfer_date))
+        .min()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal::Decimal;
+    use time::Month;
+    use crate::pages::notes_page::NoteDraft;
+
+    fn rub(minor: i64) -> Money {
+        Money::from_minor(minor, Currency::RUB)
+    }
+
+    fn usd(minor: i64) -> Money {
+        Money::from_minor(minor, Currency::USD)
+    }
+
+    fn date(month: Month, day: u8) -> Date {
+        Date::from_calendar_date(2024, month, day).unwrap()
+    }
+
+    fn draft(account: i32, price: Money, date: Date) -> NoteDraft {
+        NoteDraft {
+            name: String::new(),
+            price,
+            category: 1,
+            account: Some(account),
+            job: None,
+            date
+        }
+    }
+
+    fn transfer(from: i32, to: i32, sent: Money, received: Money) -> Transfer {
+        Transfer::new(0, TransferDraft {from, to, sent, received, date: date(Month::March, 5)})
+    }
+
+    #[test]
+    fn transfers_in_and_out() {
+        let card = Account::new(1, String::from("Карта"), rub(10_000));
+        let transfers = [transfer(1, 2, rub(3_000), rub(3_000)), transfer(3, 1, rub(500), rub(500))];
+        let incomes = [Income::new(1, 1, draft(1, rub(2_000), date(Month::March, 1))), Income::new(2, 1, draft(2, rub(9_000), date(Month::March, 1)))];
+        let expenses = [Expense::new(1, 1, draft(1, rub(1_500), date(Month::March, 2)))];
+
+        assert_eq!(balance(&card, &incomes, &expenses, &transfers, &[]), Ok(rub(8_000)));
+    }
+
+    #[test]
+    fn transfer_between_currencies_uses_sent_and_received() {
+        let card = Account::new(1, String::from("Карта"), rub(100_000));
+        let wallet = Account::new(2, String::from("Валюта"), usd(0));
+        let transfers = [transfer(1, 2, rub(90_000), usd(1_000))];
+
+        assert_eq!(balance(&card, &[], &[], &transfers, &[]), Ok(rub(10_000)));
+        assert_eq!(balance(&wallet, &[], &[], &transfers, &[]), Ok(usd(1_000)));
+    }
+
+    #[test]
+    fn foreign_income_is_converted_at_its_date() {
+        let card = Account::new(1, String::from("Карта"), rub(0));
+        let rates = [
+            ExchangeRate::new(1, Currency::USD, Currency::RUB, Decimal::from(90), date(Month::March, 1)),
+            ExchangeRate::new(2, Currency::USD, Currency::RUB, Decimal::from(95), date(Month::April, 1))
+        ];
+        let incomes = [Income::new(1, 1, draft(1, usd(1_000), date(Month::March, 20)))];
+
+        assert_eq!(balance(&card, &incomes, &[], &[], &rates), Ok(rub(90_000)));
+        assert_eq!(
+            balance(&card, &incomes, &[], &[], &rates[1..]),
+            Err(ConversionError::NoRate(Currency::USD, Currency::RUB, date(Month::March, 20)))
+        );
+    }
+
+    #[test]
+    fn first_activity_of_the_account() {
+        let card = Account::new(1, String::from("Карта"), rub(10_000));
+        let incomes = [Income::new(1, 1, draft(1, rub(100), date(Month::March, 10))), Income::new(2, 1, draft(2, rub(100), date(Month::January, 1)))];
+        let expenses = [Expense::new(1, 1, draft(1, rub(100), date(Month::March, 7)))];
+
+        assert_eq!(first_activity(&card, &incomes, &expenses, &[]), Some(date(Month::March, 7)));
+        assert_eq!(first_activity(&card, &incomes, &expenses, &[transfer(2, 1, rub(1), rub(1))]), Some(date(Month::March, 5)));
+        assert_eq!(first_activity(&card, &[], &[], &[]), None);
+    }
+}
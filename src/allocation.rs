@@ -0,0 +1,466 @@
+use std::fmt::{Display, Formatter};
+use rust_decimal::Decimal;
+use rust_decimal::prelude::ToPrimitive;
+use sqlx::FromRow;
+use time::{Date, Month};
+use crate::category::Category;
+use crate::cost_center::{CenterTotal, CostCenter, ExpenseSplit, SplitError, center_totals, unallocated};
+use crate::exchange::{ConversionError, ExchangeRate, convert};
+use crate::expense::Expense;
+use crate::money::{Currency, Money, MoneyError};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Driver {
+    Headcount,
+    //share of expenses attributed to the center directly in the same period
+    DirectCost,
+    FloorArea,
+    Custom
+}
+
+impl Driver {
+    pub const ALL: [Driver; 4] = [Driver::Headcount, Driver::DirectCost, Driver::FloorArea, Driver::Custom];
+    //Drivers which values are entered for every cost center
+    pub const ENTERED: [Driver; 3] = [Driver::Headcount, Driver::FloorArea, Driver::Custom];
+
+    //Value stored in RULE_DRIVER and DRIVER_KIND columns
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Driver::Headcount => "HEADCOUNT",
+            Driver::DirectCost => "DIRECT_COST",
+            Driver::FloorArea => "FLOOR_AREA",
+            Driver::Custom => "CUSTOM"
+        }
+    }
+}
+
+impl TryFrom<String> for Driver {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        match value.as_str() {
+            "HEADCOUNT" => Ok(Driver::Headcount),
+            "DIRECT_COST" => Ok(Driver::DirectCost),
+            "FLOOR_AREA" => Ok(Driver::FloorArea),
+            "CUSTOM" => Ok(Driver::Custom),
+            other => Err(format!("unknown allocation driver: {other}"))
+        }
+    }
+}
+
+impl Display for Driver {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Driver::Headcount => write!(f, "Численность"),
+            Driver::DirectCost => write!(f, "Доля прямых затрат"),
+            Driver::FloorArea => write!(f, "Площадь"),
+            Driver::Custom => write!(f, "Свои веса")
+        }
+    }
+}
+
+//Expenses of the category and its subcategories are overhead distributed by the driver
+#[derive(Clone, Debug, FromRow)]
+pub struct AllocationRule {
+    rule_id: i32,
+    rule_category: i32,
+    #[sqlx(try_from = "String")]
+    rule_driver: Driver
+}
+
+impl AllocationRule {
+    pub fn get_id(&self) -> i32 {
+        self.rule_id
+    }
+
+    pub fn get_category(&self) -> i32 {
+        self.rule_category
+    }
+
+    pub fn get_driver(&self) -> Driver {
+        self.rule_driver
+    }
+}
+
+pub struct RuleDraft {
+    pub category: i32,
+    pub driver: Driver
+}
+
+#[derive(Clone, Debug, FromRow)]
+pub struct DriverValue {
+    driver_center: i32,
+    #[sqlx(try_from = "String")]
+    driver_kind: Driver,
+    driver_value: Decimal
+}
+
+impl DriverValue {
+    pub fn get_center(&self) -> i32 {
+        self.driver_center
+    }
+
+    pub fn get_kind(&self) -> Driver {
+        self.driver_kind
+    }
+
+    pub fn get_value(&self) -> Decimal {
+        self.driver_value
+    }
+}
+
+pub struct DriverDraft {
+    pub center: i32,
+    pub kind: Driver,
+    pub value: Decimal
+}
+
+//Row of the allocation journal: part of the overhead category moved to the center in the month
+#[derive(Clone, Debug, FromRow)]
+pub struct Allocation {
+    allocation_id: i32,
+    //first day of the month
+    allocation_period: Date,
+    allocation_category: i32,
+    allocation_center: i32,
+    //read from `price` and `currency` columns
+    #[sqlx(flatten)]
+    allocation_amount: Money
+}
+
+impl Allocation {
+    //Allocations are saved and loaded only through the database
+    #[cfg(test)]
+    pub fn new(allocation_id: i32, period: Date, draft: AllocationDraft) -> Self {
+        Self {
+            allocation_id,
+            allocation_period: period,
+            allocation_category: draft.category,
+            allocation_center: draft.center,
+            allocation_amount: draft.amount
+        }
+    }
+
+    pub fn get_id(&self) -> i32 {
+        self.allocation_id
+    }
+
+    pub fn get_period(&self) -> Date {
+        self.allocation_period
+    }
+
+    pub fn get_category(&self) -> i32 {
+        self.allocation_category
+    }
+
+    pub fn get_center(&self) -> i32 {
+        self.allocation_center
+    }
+
+    pub fn get_amount(&self) -> Money {
+        self.allocation_amount
+    }
+}
+
+pub struct AllocationDraft {
+    pub category: i32,
+    pub center: i32,
+    pub amount: Money
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum AllocationError {
+    //no center has a positive value of the driver, so there is nothing to distribute by
+    NoWeights(Driver),
+    Split(SplitError)
+}
+
+impl Display for AllocationError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AllocationError::NoWeights(driver) => write!(f, "Ни у одного центра затрат не задан драйвер «{driver}»"),
+            AllocationError::Split(err) => write!(f, "{err}")
+        }
+    }
+}
+
+impl From<SplitError> for AllocationError {
+    fn from(value: SplitError) -> Self {
+        AllocationError::Split(value)
+    }
+}
+
+impl From<ConversionError> for AllocationError {
+    fn from(value: ConversionError) -> Self {
+        AllocationError::Split(SplitError::Conversion(value))
+    }
+}
+
+impl From<MoneyError> for AllocationError {
+    fn from(value: MoneyError) -> Self {
+        AllocationError::Split(SplitError::from(value))
+    }
+}
+
+//First and last days of the month
+pub fn period_bounds((year, month): (i32, Month)) -> (Date, Date) {
+    let last_day = time::util::days_in_year_month(year, month);
+
+    (
+        Date::from_calendar_date(year, month, 1).unwrap(),
+        Date::from_calendar_date(year, month, last_day).unwrap()
+    )
+}
+
+//Allocation of the month is recorded in the journal under this id, e.g. 202403
+pub fn period_id((year, month): (i32, Month)) -> i32 {
+    year * 100 + u8::from(month) as i32
+}
+
+//Rule of the category itself wins over the rule of its parent, so no expense is allocated twice
+pub fn rule_for<'a>(rules: &'a [AllocationRule], categories: &[Category], category_id: i32) -> Option<&'a AllocationRule> {
+    let parent = categories
+        .iter()
+        .find(|category| category.get_id() == category_id)
+        .and_then(|category| category.get_parent());
+
+    rules
+        .iter()
+        .find(|rule| rule.rule_category == category_id)
+        .or_else(|| rules.iter().find(|rule| Some(rule.rule_category) == parent))
+}
+
+//Weight of every center, centers without a value of the driver get nothing
+pub fn weights(driver: Driver, centers: &[CostCenter], drivers: &[DriverValue], direct: &[CenterTotal]) -> Vec<(i32, Decimal)> {
+    centers
+        .iter()
+        .map(|center| {
+            let weight = match driver {
+                Driver::DirectCost => direct
+                    .iter()
+                    .find(|total| total.center == Some(center.get_id()))
+                    .map_or(Decimal::ZERO, |total| Decimal::from(total.total.minor())),
+                driver => drivers
+                    .iter()
+                    .find(|value| value.driver_center == center.get_id() && value.driver_kind == driver)
+                    .map_or(Decimal::ZERO, |value| value.driver_value)
+            };
+
+            (center.get_id(), weight.max(Decimal::ZERO))
+        })
+        .collect()
+}
+
+//Amount is split in proportion to the weights, see largest_remainder
+pub fn distribute(amount: Money, weights: &[(i32, Decimal)], driver: Driver) -> Result<Vec<(i32, Money)>, AllocationError> {
+    if weights.iter().map(|(_, weight)| *weight).sum::<Decimal>() <= Decimal::ZERO {
+        return Err(AllocationError::NoWeights(driver))
+    }
+
+    Ok(largest_remainder(amount, weights)?)
+}
+
+//Amount is split in proportion to the weights, their total must be positive. Minor units left after rounding
+//go to the parts with the largest fractional parts, so the parts always add up to the amount exactly.
+pub fn largest_remainder(amount: Money, weights: &[(i32, Decimal)]) -> Result<Vec<(i32, Money)>, MoneyError> {
+    let total_weight = weights.iter().map(|(_, weight)| *weight).sum::<Decimal>();
+    let whole = Decimal::from(amount.minor().abs());
+
+    let mut parts = weights
+        .iter()
+        .map(|(id, weight)| {
+            let exact = whole
+                .checked_mul(*weight)
+                .ok_or(MoneyError::Overflow)?
+                / total_weight;
+            Ok((*id, exact.trunc(), exact.fract()))
+        })
+        .collect::<Result<Vec<(i32, Decimal, Decimal)>, MoneyError>>()?;
+
+    let distributed = parts.iter().map(|(_, part, _)| *part).sum::<Decimal>();
+    let left = (whole - distributed).to_usize().unwrap_or(0);
+
+    let mut by_fraction = (0..parts.len()).collect::<Vec<usize>>();
+    by_fraction.sort_by(|a, b| parts[*b].2.cmp(&parts[*a].2));
+
+    for index in by_fraction.into_iter().take(left) {
+        parts[index].1 += Decimal::ONE;
+    }
+
+    let sign = if amount.is_negative() {-1} else {1};
+
+    parts
+        .into_iter()
+        .map(|(id, part, _)| {
+            let minor = part.to_i64().ok_or(MoneyError::Overflow)?;
+            Ok((id, Money::from_minor(sign * minor, amount.currency())))
+        })
+        .collect()
+}
+
+//Everything allocation is calculated from
+pub struct AllocationInputs<'a> {
+    pub rules: &'a [AllocationRule],
+    pub centers: &'a [CostCenter],
+    pub drivers: &'a [DriverValue],
+    pub expenses: &'a [Expense],
+    pub splits: &'a [ExpenseSplit],
+    pub categories: &'a [Category],
+    pub rates: &'a [ExchangeRate]
+}
+
+//Distributes the part of overhead expenses of the month which isn't attributed to centers directly.
+//Direct cost share is taken from the expenses which aren't covered by any rule.
+pub fn run_allocation(inputs: &AllocationInputs<'_>, period: (i32, Month), currency: Currency) -> Result<Vec<AllocationDraft>, AllocationError> {
+    let AllocationInputs {rules, centers, drivers, expenses, splits, categories, rates} = *inputs;
+    let (from, to) = period_bounds(period);
+
+    let in_period = expenses
+        .iter()
+        .filter(|expense| expense.get_date() >= from && expense.get_date() <= to)
+        .collect::<Vec<&Expense>>();
+
+    let direct_expenses = in_period
+        .iter()
+        .filter(|expense| rule_for(rules, categories, expense.get_category()).is_none())
+        .map(|expense| (*expense).clone())
+        .collect::<Vec<Expense>>();
+
+    let direct = center_totals(centers, &direct_expenses, splits, rates, Some(from), Some(to), currency)?.complete()?;
+
+    let mut drafts = Vec::new();
+
+    for rule in rules {
+        let mut pool = Money::zero(currency);
+
+        for expense in in_period.iter().filter(|expense| rule_for(rules, categories, expense.get_category()).map(|rule| rule.rule_id) == Some(rule.rule_id)) {
+            pool = pool.checked_add(convert(rates, unallocated(expense, splits)?, currency, expense.get_date())?)?;
+        }
+
+        if pool.is_zero() {
+            continue
+        }
+
+        for (center, amount) in distribute(pool, &weights(rule.rule_driver, centers, drivers, &direct), rule.rule_driver)? {
+            drafts.push(AllocationDraft {
+                category: rule.rule_category,
+                center,
+                amount
+            });
+        }
+    }
+
+    Ok(drafts)
+}
+
+pub struct CenterComparison {
+    //None for the part of expenses which isn't attributed to any center
+    pub center: Option<i32>,
+    pub before: Money,
+    pub overhead: Money,
+    pub after: Money
+}
+
+//Totals of centers in the month before and after the saved allocation of that month
+pub fn before_after(centers: &[CostCenter], expenses: &[Expense], splits: &[ExpenseSplit], allocations: &[Allocation], rates: &[ExchangeRate], period: (i32, Month), currency: Currency) -> Result<Vec<CenterComparison>, AllocationError> {
+    let (from, to) = period_bounds(period);
+    let before = center_totals(centers, expenses, splits, rates, Some(from), Some(to), currency)?.complete()?;
+
+    let allocated_to = |center: Option<i32>| -> Result<Money, AllocationError> {
+        let mut allocated = Money::zero(currency);
+
+        for allocation in allocations.iter().filter(|allocation| allocation.allocation_period == from) {
+            if center.is_none() || center == Some(allocation.allocation_center) {
+                allocated = allocated.checked_add(convert(rates, allocation.allocation_amount, currency, to)?)?;
+            }
+        }
+
+        Ok(allocated)
+    };
+
+    before
+        .into_iter()
+        .map(|total| {
+            //Everything allocated to centers is taken from the unattributed part
+            let overhead = match total.center {
+                Some(_) => allocated_to(total.center)?,
+                None => allocated_to(None)?.checked_neg()?
+            };
+
+            Ok(CenterComparison {
+                center: total.center,
+                before: total.total,
+                overhead,
+                after: total.total.checked_add(overhead)?
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rub(minor: i64) -> Money {
+        Money::from_minor(minor, Currency::RUB)
+    }
+
+    fn weights_of(values: &[(i32, i64)]) -> Vec<(i32, Decimal)> {
+        values.iter().map(|(center, weight)| (*center, Decimal::from(*weight))).collect()
+    }
+
+    fn total(parts: &[(i32, Money)]) -> i64 {
+        parts.iter().map(|(_, amount)| amount.minor()).sum()
+    }
+
+    #[test]
+    fn parts_add_up_to_the_pool() {
+        let parts = distribute(rub(10_000), &weights_of(&[(1, 1), (2, 1), (3, 1)]), Driver::Headcount).unwrap();
+
+        assert_eq!(parts, vec![(1, rub(3_334)), (2, rub(3_333)), (3, rub(3_333))]);
+        assert_eq!(total(&parts), 10_000);
+    }
+
+    #[test]
+    fn leftover_goes_to_largest_remainders() {
+        //Exact parts are 3.5, 2.1 and 1.4 kopecks
+        let parts = distribute(rub(7), &weights_of(&[(1, 5), (2, 3), (3, 2)]), Driver::FloorArea).unwrap();
+
+        assert_eq!(parts, vec![(1, rub(4)), (2, rub(2)), (3, rub(1))]);
+    }
+
+    #[test]
+    fn odd_pools_and_weights_always_sum_exactly() {
+        let weights = weights_of(&[(1, 7), (2, 13), (3, 0), (4, 29), (5, 1)]);
+
+        for pool in [1, 2, 3, 99, 1_001, 123_457, -50_003] {
+            let parts = distribute(rub(pool), &weights, Driver::Custom).unwrap();
+
+            assert_eq!(total(&parts), pool, "{pool}");
+            assert_eq!(parts[2].1, rub(0));
+        }
+    }
+
+    #[test]
+    fn refunds_are_distributed_with_their_sign() {
+        let parts = distribute(rub(-100), &weights_of(&[(1, 1), (2, 3)]), Driver::Headcount).unwrap();
+
+        assert_eq!(parts, vec![(1, rub(-25)), (2, rub(-75))]);
+    }
+
+    #[test]
+    fn nothing_to_distribute_by() {
+        let result = distribute(rub(100), &weights_of(&[(1, 0), (2, 0)]), Driver::DirectCost);
+
+        assert_eq!(result, Err(AllocationError::NoWeights(Driver::DirectCost)));
+    }
+
+    #[test]
+    fn period_bounds_follow_the_calendar() {
+        assert_eq!(
+            period_bounds((2024, Month::February)),
+            (Date::from_calendar_date(2024, Month::February, 1).unwrap(), Date::from_calendar_date(2024, Month::February, 29).unwrap())
+        );
+        assert_eq!(period_id((2024, Month::March)), 202403);
+    }
+}
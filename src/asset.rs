@@ -0,0 +1,376 @@
+use std::fmt::{Display, Formatter};
+use rust_decimal::{Decimal, RoundingStrategy};
+use sqlx::{FromRow, Row};
+use sqlx::mysql::MySqlRow;
+use time::Date;
+use crate::allocation::period_bounds;
+use crate::budget::shift_month;
+use crate::money::{Money, MoneyError};
+
+//Declining balance depreciates twice as fast as straight line at the start
+pub const DECLINING_FACTOR: i64 = 2;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DepreciationMethod {
+    StraightLine,
+    //fixed share of the book value, switches to straight line when that gives more
+    DecliningBalance,
+    //in proportion to the units produced in the month
+    UnitsOfProduction
+}
+
+impl DepreciationMethod {
+    pub const ALL: [DepreciationMethod; 3] = [DepreciationMethod::StraightLine, DepreciationMethod::DecliningBalance, DepreciationMethod::UnitsOfProduction];
+
+    //Value stored in ASSET_METHOD column
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DepreciationMethod::StraightLine => "STRAIGHT_LINE",
+            DepreciationMethod::DecliningBalance => "DECLINING",
+            DepreciationMethod::UnitsOfProduction => "UNITS"
+        }
+    }
+}
+
+impl TryFrom<String> for DepreciationMethod {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        match value.as_str() {
+            "STRAIGHT_LINE" => Ok(DepreciationMethod::StraightLine),
+            "DECLINING" => Ok(DepreciationMethod::DecliningBalance),
+            "UNITS" => Ok(DepreciationMethod::UnitsOfProduction),
+            other => Err(format!("unknown depreciation method: {other}"))
+        }
+    }
+}
+
+impl Display for DepreciationMethod {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DepreciationMethod::StraightLine => write!(f, "Линейный"),
+            DepreciationMethod::DecliningBalance => write!(f, "Уменьшаемого остатка"),
+            DepreciationMethod::UnitsOfProduction => write!(f, "Пропорционально выработке")
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Asset {
+    asset_id: i32,
+    asset_name: String,
+    asset_cost: Money,
+    //value expected to be left at the end of the useful life, in the currency of the cost
+    asset_salvage: Money,
+    asset_acquired: Date,
+    //in months
+    asset_life: u32,
+    asset_method: DepreciationMethod,
+    //units expected to be produced over the life, set only for units-of-production
+    asset_units: Option<Decimal>,
+    //depreciation is posted as expenses of this category
+    asset_category: i32
+}
+
+impl Asset {
+    pub fn new(asset_id: i32, draft: AssetDraft) -> Self {
+        Self {
+            asset_id,
+            asset_name: draft.name,
+            asset_cost: draft.cost,
+            asset_salvage: draft.salvage,
+            asset_acquired: draft.acquired,
+            asset_life: draft.life,
+            asset_method: draft.method,
+            asset_units: draft.units,
+            asset_category: draft.category
+        }
+    }
+
+    pub fn get_id(&self) -> i32 {
+        self.asset_id
+    }
+
+    pub fn get_name(&self) -> &str {
+        &self.asset_name
+    }
+
+    pub fn get_cost(&self) -> Money {
+        self.asset_cost
+    }
+
+    pub fn get_salvage(&self) -> Money {
+        self.asset_salvage
+    }
+
+    pub fn get_acquired(&self) -> Date {
+        self.asset_acquired
+    }
+
+    pub fn get_life(&self) -> u32 {
+        self.asset_life
+    }
+
+    pub fn get_method(&self) -> DepreciationMethod {
+        self.asset_method
+    }
+
+    pub fn get_units(&self) -> Option<Decimal> {
+        self.asset_units
+    }
+
+    pub fn get_category(&self) -> i32 {
+        self.asset_category
+    }
+
+    //Name of the expenses depreciation of the asset is posted with
+    pub fn expense_name(&self) -> String {
+        format!("Амортизация: {}", self.asset_name)
+    }
+}
+
+//Queries alias the cost and the salvage value to `price` and `salvage`, both are in `currency`
+impl<'r> FromRow<'r, MySqlRow> for Asset {
+    fn from_row(row: &'r MySqlRow) -> Result<Self, sqlx::Error> {
+        let method: String = row.try_get("asset_method")?;
+
+        Ok(Asset {
+            asset_id: row.try_get("asset_id")?,
+            asset_name: row.try_get("asset_name")?,
+            asset_cost: Money::from_columns(row, "price", "currency")?,
+            asset_salvage: Money::from_columns(row, "salvage", "currency")?,
+            asset_acquired: row.try_get("asset_acquired")?,
+            asset_life: row.try_get("asset_life")?,
+            asset_method: DepreciationMethod::try_from(method).map_err(|err| sqlx::Error::Decode(err.into()))?,
+            asset_units: row.try_get("asset_units")?,
+            asset_category: row.try_get("asset_category")?
+        })
+    }
+}
+
+pub struct AssetDraft {
+    pub name: String,
+    pub cost: Money,
+    pub salvage: Money,
+    pub acquired: Date,
+    pub life: u32,
+    pub method: DepreciationMethod,
+    pub units: Option<Decimal>,
+    pub category: i32
+}
+
+//Units produced by the asset in the month, month is stored as its first day
+#[derive(Clone, Debug, FromRow)]
+pub struct Usage {
+    usage_asset: i32,
+    usage_month: Date,
+    usage_units: Decimal
+}
+
+impl Usage {
+    pub fn new(usage_asset: i32, usage_month: Date, usage_units: Decimal) -> Self {
+        Self {
+            usage_asset,
+            usage_month,
+            usage_units
+        }
+    }
+
+    pub fn get_asset(&self) -> i32 {
+        self.usage_asset
+    }
+
+    pub fn get_month(&self) -> Date {
+        self.usage_month
+    }
+
+    pub fn get_units(&self) -> Decimal {
+        self.usage_units
+    }
+}
+
+pub struct ScheduleRow {
+    //last day of the month, depreciation expense is dated with it
+    pub date: Date,
+    //None for units-of-production months without recorded usage and for other methods
+    pub units: Option<Decimal>,
+    pub depreciation: Money,
+    pub accumulated: Money,
+    pub book_value: Money
+}
+
+//Depreciation starts in the month after the acquisition and lasts for the useful life.
+//Every method brings the book value down to the salvage value and never below it,
+//units-of-production gets there only if the expected units are produced within the life.
+pub fn schedule(asset: &Asset, usage: &[Usage]) -> Result<Vec<ScheduleRow>, MoneyError> {
+    let currency = asset.asset_cost.currency();
+    let depreciable = asset.asset_cost.checked_sub(asset.asset_salvage)?.to_decimal();
+    let life = Decimal::from(asset.asset_life);
+
+    let mut month = (asset.asset_acquired.year(), asset.asset_acquired.month());
+    let mut accumulated = Decimal::ZERO;
+    let mut rows = Vec::with_capacity(asset.asset_life as usize);
+
+    for index in 1..=asset.asset_life {
+        month = shift_month(month, true);
+        let (first_day, last_day) = period_bounds(month);
+        let remaining = depreciable - accumulated;
+        let months_left = Decimal::from(asset.asset_life - index + 1);
+
+        let units = match asset.asset_method {
+            DepreciationMethod::UnitsOfProduction => usage
+                .iter()
+                .find(|usage| usage.usage_asset == asset.asset_id && usage.usage_month == first_day)
+                .map(|usage| usage.usage_units),
+            _ => None
+        };
+
+        let depreciation = match asset.asset_method {
+            DepreciationMethod::StraightLine => match index == asset.asset_life {
+                true => remaining,
+                false => round(depreciable / life)
+            },
+            DepreciationMethod::DecliningBalance => {
+                let book_value = asset.asset_cost.to_decimal() - accumulated;
+                let declining = book_value
+                    .checked_mul(Decimal::from(DECLINING_FACTOR))
+                    .ok_or(MoneyError::Overflow)?
+                    / life;
+
+                round(declining.max(remaining / months_left))
+            }
+            DepreciationMethod::UnitsOfProduction => match (units, asset.asset_units) {
+                (Some(units), Some(total)) if !total.is_zero() => round(
+                    depreciable
+                        .checked_mul(units)
+                        .ok_or(MoneyError::Overflow)?
+                        / total
+                ),
+                _ => Decimal::ZERO
+            }
+        }.min(remaining);
+
+        accumulated += depreciation;
+
+        rows.push(ScheduleRow {
+            date: last_day,
+            units,
+            depreciation: Money::from_decimal(depreciation, currency)?,
+            accumulated: Money::from_decimal(accumulated, currency)?,
+            book_value: Money::from_decimal(asset.asset_cost.to_decimal() - accumulated, currency)?
+        });
+    }
+
+    Ok(rows)
+}
+
+//Months which are over by the date and have depreciation to post
+pub fn due(asset: &Asset, usage: &[Usage], today: Date) -> Result<Vec<ScheduleRow>, MoneyError> {
+    Ok(schedule(asset, usage)?
+        .into_iter()
+        .filter(|row| row.date <= today && !row.depreciation.is_zero())
+        .collect())
+}
+
+//Book value at the end of the date
+pub fn book_value(asset: &Asset, usage: &[Usage], date: Date) -> Result<Money, MoneyError> {
+    let value = schedule(asset, usage)?
+        .into_iter()
+        .take_while(|row| row.date <= date)
+        .last()
+        .map_or(asset.asset_cost, |row| row.book_value);
+
+    Ok(value)
+}
+
+fn round(amount: Decimal) -> Decimal {
+    amount.round_dp_with_strategy(2, RoundingStrategy::MidpointAwayFromZero)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use time::Month;
+    use crate::money::Currency;
+
+    fn date(month: Month, day: u8) -> Date {
+        Date::from_calendar_date(2024, month, day).unwrap()
+    }
+
+    fn rub(minor: i64) -> Money {
+        Money::from_minor(minor, Currency::RUB)
+    }
+
+    //Cost 1000, salvage 100, acquired in January so depreciation starts in February
+    fn asset(life: u32, method: DepreciationMethod, units: Option<Decimal>) -> Asset {
+        Asset::new(1, AssetDraft {
+            name: String::from("Станок"),
+            cost: rub(100_000),
+            salvage: rub(10_000),
+            acquired: date(Month::January, 20),
+            life,
+            method,
+            units,
+            category: 1
+        })
+    }
+
+    fn depreciation(rows: &[ScheduleRow]) -> Vec<Money> {
+        rows.iter().map(|row| row.depreciation).collect()
+    }
+
+    #[test]
+    fn straight_line_ends_at_salvage() {
+        let rows = schedule(&asset(7, DepreciationMethod::StraightLine, None), &[]).unwrap();
+
+        assert_eq!(rows.len(), 7);
+        assert_eq!(rows[0].date, date(Month::February, 29));
+        assert_eq!(rows[0].depreciation, rub(12_857));
+        //The last month takes the rounding remainder
+        assert_eq!(rows[6].depreciation, rub(12_858));
+        assert_eq!(rows[6].accumulated, rub(90_000));
+        assert_eq!(rows[6].book_value, rub(10_000));
+    }
+
+    #[test]
+    fn declining_balance_switches_to_straight_line() {
+        let rows = schedule(&asset(5, DepreciationMethod::DecliningBalance, None), &[]).unwrap();
+
+        assert_eq!(depreciation(&rows), [rub(40_000), rub(24_000), rub(14_400), rub(8_640), rub(2_960)]);
+        assert_eq!(rows[4].book_value, rub(10_000));
+    }
+
+    #[test]
+    fn units_of_production_never_goes_below_salvage() {
+        let usage = [
+            Usage::new(1, date(Month::February, 1), Decimal::from(600)),
+            Usage::new(2, date(Month::March, 1), Decimal::from(600)),
+            Usage::new(1, date(Month::April, 1), Decimal::from(600))
+        ];
+        let rows = schedule(&asset(3, DepreciationMethod::UnitsOfProduction, Some(Decimal::from(1000))), &usage).unwrap();
+
+        assert_eq!(depreciation(&rows), [rub(54_000), rub(0), rub(36_000)]);
+        assert_eq!(rows[1].units, None);
+        assert_eq!(rows[2].book_value, rub(10_000));
+    }
+
+    #[test]
+    fn due_takes_finished_months_with_depreciation() {
+        let straight_line = asset(7, DepreciationMethod::StraightLine, None);
+        let by_units = asset(3, DepreciationMethod::UnitsOfProduction, Some(Decimal::from(1000)));
+        let usage = [Usage::new(1, date(Month::February, 1), Decimal::from(100))];
+
+        assert_eq!(due(&straight_line, &[], date(Month::March, 30)).unwrap().len(), 1);
+        assert_eq!(due(&straight_line, &[], date(Month::March, 31)).unwrap().len(), 2);
+        assert_eq!(due(&by_units, &usage, date(Month::December, 31)).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn book_value_on_date() {
+        let asset = asset(7, DepreciationMethod::StraightLine, None);
+
+        assert_eq!(book_value(&asset, &[], date(Month::February, 28)).unwrap(), rub(100_000));
+        assert_eq!(book_value(&asset, &[], date(Month::March, 15)).unwrap(), rub(87_143));
+        assert_eq!(book_value(&asset, &[], date(Month::December, 31)).unwrap(), rub(10_000));
+    }
+}
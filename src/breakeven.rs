@@ -0,0 +1,306 @@
+use std::fmt::{Display, Formatter};
+use rust_decimal::{Decimal, RoundingStrategy};
+use rust_decimal::prelude::ToPrimitive;
+use sqlx::FromRow;
+use time::Date;
+use crate::category::Category;
+use crate::exchange::{ConversionError, ExchangeRate, total_in};
+use crate::expense::Expense;
+use crate::income::Income;
+use crate::money::{Currency, Money, MoneyError};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CostBehavior {
+    Fixed,
+    //grows together with the volume of sales
+    Variable
+}
+
+impl CostBehavior {
+    pub const ALL: [CostBehavior; 2] = [CostBehavior::Fixed, CostBehavior::Variable];
+
+    //Value stored in BEHAVIOR_KIND column
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CostBehavior::Fixed => "FIXED",
+            CostBehavior::Variable => "VARIABLE"
+        }
+    }
+}
+
+impl TryFrom<String> for CostBehavior {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        match value.as_str() {
+            "FIXED" => Ok(CostBehavior::Fixed),
+            "VARIABLE" => Ok(CostBehavior::Variable),
+            other => Err(format!("unknown cost behavior: {other}"))
+        }
+    }
+}
+
+impl Display for CostBehavior {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CostBehavior::Fixed => write!(f, "Постоянные"),
+            CostBehavior::Variable => write!(f, "Переменные")
+        }
+    }
+}
+
+#[derive(Clone, Debug, FromRow)]
+pub struct CategoryBehavior {
+    behavior_category: i32,
+    #[sqlx(try_from = "String")]
+    behavior_kind: CostBehavior
+}
+
+impl CategoryBehavior {
+    pub fn new(behavior_category: i32, behavior_kind: CostBehavior) -> Self {
+        Self {
+            behavior_category,
+            behavior_kind
+        }
+    }
+
+    pub fn get_category(&self) -> i32 {
+        self.behavior_category
+    }
+
+    pub fn get_kind(&self) -> CostBehavior {
+        self.behavior_kind
+    }
+}
+
+//Classification of the category itself wins over the one of its parent, None if neither is classified
+pub fn behavior_of(behaviors: &[CategoryBehavior], categories: &[Category], category_id: i32) -> Option<CostBehavior> {
+    let parent = categories
+        .iter()
+        .find(|category| category.get_id() == category_id)
+        .and_then(|category| category.get_parent());
+
+    behaviors
+        .iter()
+        .find(|behavior| behavior.behavior_category == category_id)
+        .or_else(|| behaviors.iter().find(|behavior| Some(behavior.behavior_category) == parent))
+        .map(|behavior| behavior.behavior_kind)
+}
+
+//Changes of the price and of the volume of sales in percents, e.g. 10 is +10%
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct WhatIf {
+    pub price_change: i32,
+    pub volume_change: i32
+}
+
+//Everything the analysis is calculated from
+pub struct BreakEvenInputs<'a> {
+    pub incomes: &'a [Income],
+    pub expenses: &'a [Expense],
+    pub categories: &'a [Category],
+    pub behaviors: &'a [CategoryBehavior],
+    pub rates: &'a [ExchangeRate]
+}
+
+pub struct BreakEven {
+    pub revenue: Money,
+    pub variable_costs: Money,
+    //includes the unclassified costs
+    pub fixed_costs: Money,
+    //expenses of categories which are neither fixed nor variable, they are counted as fixed
+    pub unclassified: Money,
+    pub contribution_margin: Money,
+    //share of the revenue left after variable costs, None without revenue
+    pub contribution_ratio: Option<Decimal>,
+    //None when every sale loses money or there is no revenue
+    pub break_even_revenue: Option<Money>,
+    pub margin_of_safety: Option<Money>,
+    pub operating_income: Money
+}
+
+impl BreakEven {
+    pub fn contribution_percent(&self) -> Option<f64> {
+        self.contribution_ratio.and_then(|ratio| (ratio * Decimal::ONE_HUNDRED).to_f64())
+    }
+
+    //Share of the revenue sales may drop by before the loss starts
+    pub fn safety_percent(&self) -> Option<f64> {
+        match (self.margin_of_safety, self.revenue.minor()) {
+            (Some(margin), revenue) if revenue > 0 => Some(margin.minor() as f64 * 100.0 / revenue as f64),
+            _ => None
+        }
+    }
+}
+
+//Incomes and expenses dated within the range (both ends included, None means unbounded) are taken in the currency.
+//What-if changes the price of every sale and the volume of sales, variable costs follow the volume only.
+pub fn analyze(inputs: &BreakEvenInputs<'_>, from: Option<Date>, to: Option<Date>, currency: Currency, what_if: WhatIf) -> Result<BreakEven, ConversionError> {
+    let BreakEvenInputs {incomes, expenses, categories, behaviors, rates} = *inputs;
+    let in_range = |date: Date| !matches!(from, Some(from) if date < from) && !matches!(to, Some(to) if date > to);
+
+    let revenue = total_in(
+        rates,
+        incomes.iter().filter(|income| in_range(income.get_date())).map(|income| (income.get_price(), income.get_date())),
+        currency
+    )?;
+
+    let costs_of = |behavior: Option<CostBehavior>| total_in(
+        rates,
+        expenses
+            .iter()
+            .filter(|expense| in_range(expense.get_date()) && behavior_of(behaviors, categories, expense.get_category()) == behavior)
+            .map(|expense| (expense.get_price(), expense.get_date())),
+        currency
+    );
+
+    let variable_costs = costs_of(Some(CostBehavior::Variable))?;
+    let unclassified = costs_of(None)?;
+    let fixed_costs = costs_of(Some(CostBehavior::Fixed))?.checked_add(unclassified)?;
+
+    let price = percent_factor(what_if.price_change);
+    let volume = percent_factor(what_if.volume_change);
+
+    let revenue = scale(revenue, price.checked_mul(volume).ok_or(MoneyError::Overflow)?)?;
+    let variable_costs = scale(variable_costs, volume)?;
+
+    let contribution_margin = revenue.checked_sub(variable_costs)?;
+    let contribution_ratio = match revenue.is_zero() {
+        true => None,
+        false => Some(contribution_margin.to_decimal() / revenue.to_decimal())
+    };
+
+    let break_even_revenue = match contribution_ratio {
+        Some(ratio) if ratio > Decimal::ZERO => Some(scale(fixed_costs, Decimal::ONE / ratio)?),
+        _ => None
+    };
+
+    let margin_of_safety = match break_even_revenue {
+        Some(break_even) => Some(revenue.checked_sub(break_even)?),
+        None => None
+    };
+
+    Ok(BreakEven {
+        revenue,
+        variable_costs,
+        fixed_costs,
+        unclassified,
+        contribution_margin,
+        contribution_ratio,
+        break_even_revenue,
+        margin_of_safety,
+        operating_income: contribution_margin.checked_sub(fixed_costs)?
+    })
+}
+
+fn percent_factor(change: i32) -> Decimal {
+    Decimal::ONE + Decimal::from(change) / Decimal::ONE_HUNDRED
+}
+
+fn scale(amount: Money, factor: Decimal) -> Result<Money, MoneyError> {
+    let scaled = amount
+        .to_decimal()
+        .checked_mul(factor)
+        .ok_or(MoneyError::Overflow)?;
+
+    Money::from_decimal(scaled.round_dp_with_strategy(2, RoundingStrategy::MidpointAwayFromZero), amount.currency())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use time::Month;
+    use crate::category::CategoryKind;
+    use crate::pages::notes_page::NoteDraft;
+
+    fn rub(minor: i64) -> Money {
+        Money::from_minor(minor, Currency::RUB)
+    }
+
+    fn draft(category: i32, minor: i64) -> NoteDraft {
+        NoteDraft {
+            name: String::new(),
+            price: rub(minor),
+            category,
+            account: None,
+            job: None,
+            date: Date::from_calendar_date(2024, Month::March, 10).unwrap()
+        }
+    }
+
+    fn category(id: i32, parent: Option<i32>) -> Category {
+        Category::new(id, None, String::new(), CategoryKind::Expense, String::new(), String::new(), parent)
+    }
+
+    //Revenue 1000, variable costs 400 (one of them in a subcategory), fixed 300, unclassified 100
+    fn analyze_with(incomes: &[Income], what_if: WhatIf) -> BreakEven {
+        let expenses = [
+            Expense::new(1, 1, draft(2, 30_000)),
+            Expense::new(2, 1, draft(5, 10_000)),
+            Expense::new(3, 1, draft(3, 30_000)),
+            Expense::new(4, 1, draft(4, 10_000))
+        ];
+        let categories = [category(2, None), category(3, None), category(4, None), category(5, Some(2))];
+        let behaviors = [CategoryBehavior::new(2, CostBehavior::Variable), CategoryBehavior::new(3, CostBehavior::Fixed)];
+
+        let inputs = BreakEvenInputs {incomes, expenses: &expenses, categories: &categories, behaviors: &behaviors, rates: &[]};
+        analyze(&inputs, None, None, Currency::RUB, what_if).unwrap()
+    }
+
+    fn revenue() -> Vec<Income> {
+        vec![Income::new(1, 1, draft(1, 100_000))]
+    }
+
+    #[test]
+    fn unclassified_costs_count_as_fixed() {
+        let analysis = analyze_with(&revenue(), WhatIf::default());
+
+        assert_eq!(analysis.variable_costs, rub(40_000));
+        assert_eq!(analysis.unclassified, rub(10_000));
+        assert_eq!(analysis.fixed_costs, rub(40_000));
+        assert_eq!(analysis.contribution_margin, rub(60_000));
+        assert_eq!(analysis.contribution_percent(), Some(60.0));
+        assert_eq!(analysis.operating_income, rub(20_000));
+    }
+
+    #[test]
+    fn break_even_revenue_is_rounded_to_kopecks() {
+        let analysis = analyze_with(&revenue(), WhatIf::default());
+
+        //400 / 0.6 = 666.666...
+        assert_eq!(analysis.break_even_revenue, Some(rub(66_667)));
+        assert_eq!(analysis.margin_of_safety, Some(rub(33_333)));
+    }
+
+    #[test]
+    fn what_if_changes_price_and_volume() {
+        let analysis = analyze_with(&revenue(), WhatIf {price_change: 10, volume_change: -20});
+
+        assert_eq!(analysis.revenue, rub(88_000));
+        assert_eq!(analysis.variable_costs, rub(32_000));
+        assert_eq!(analysis.fixed_costs, rub(40_000));
+        assert_eq!(analysis.break_even_revenue, Some(rub(62_857)));
+        assert_eq!(analysis.operating_income, rub(16_000));
+    }
+
+    #[test]
+    fn no_break_even_without_revenue_or_contribution() {
+        let without_revenue = analyze_with(&[], WhatIf::default());
+        let losing = analyze_with(&[Income::new(1, 1, draft(1, 30_000))], WhatIf::default());
+
+        assert_eq!(without_revenue.contribution_ratio, None);
+        assert_eq!(without_revenue.break_even_revenue, None);
+        assert_eq!(losing.break_even_revenue, None);
+        assert_eq!(losing.safety_percent(), None);
+    }
+
+    #[test]
+    fn own_classification_wins_over_the_parent() {
+        let categories = [category(2, None), category(5, Some(2)), category(6, Some(2))];
+        let behaviors = [CategoryBehavior::new(2, CostBehavior::Variable), CategoryBehavior::new(5, CostBehavior::Fixed)];
+
+        assert_eq!(behavior_of(&behaviors, &categories, 5), Some(CostBehavior::Fixed));
+        assert_eq!(behavior_of(&behaviors, &categories, 6), Some(CostBehavior::Variable));
+        assert_eq!(behavior_of(&behaviors, &categories, 7), None);
+    }
+}
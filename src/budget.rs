@@ -0,0 +1,201 @@
+use sqlx::FromRow;
+use time::{Date, Month};
+use crate::category::Category;
+use crate::exchange::{ConversionError, ExchangeRate, total_in};
+use crate::expense::Expense;
+use crate::money::Money;
+use crate::recurring::today;
+
+//Limit of spending in a category (together with its subcategories) for every month
+#[derive(Clone, Debug, FromRow)]
+pub struct Budget {
+    budget_id: i32,
+    budget_category: i32,
+    //read from `price` and `currency` columns
+    #[sqlx(flatten)]
+    budget_limit: Money
+}
+
+impl Budget {
+    pub fn new(budget_id: i32, budget_category: i32, budget_limit: Money) -> Self {
+        Self {
+            budget_id,
+            budget_category,
+            budget_limit
+        }
+    }
+
+    pub fn get_id(&self) -> i32 {
+        self.budget_id
+    }
+
+    pub fn get_category(&self) -> i32 {
+        self.budget_category
+    }
+
+    pub fn get_limit(&self) -> Money {
+        self.budget_limit
+    }
+
+    //Expenses of subcategories are counted in the budget of their parent
+    pub fn covers(&self, category_id: i32, categories: &[Category]) -> bool {
+        category_id == self.budget_category || categories
+            .iter()
+            .any(|category| category.get_id() == category_id && category.get_parent() == Some(self.budget_category))
+    }
+}
+
+pub struct BudgetDraft {
+    pub category: i32,
+    pub limit: Money
+}
+
+pub struct BudgetUsage<'a> {
+    pub budget: &'a Budget,
+    //spent in the currency of the limit
+    pub spent: Result<Money, ConversionError>
+}
+
+impl BudgetUsage<'_> {
+    pub fn is_exceeded(&self) -> bool {
+        matches!(&self.spent, Ok(spent) if spent.minor() > self.budget.budget_limit.minor())
+    }
+
+    //Share of the limit which is spent, in percents. May be more than 100
+    pub fn percent(&self) -> f32 {
+        match (&self.spent, self.budget.budget_limit.minor()) {
+            (Ok(spent), limit) if limit > 0 => spent.minor() as f32 * 100.0 / limit as f32,
+            _ => 0.0
+        }
+    }
+}
+
+pub fn month_usage<'a>(budgets: &'a [Budget], expenses: &[Expense], categories: &[Category], rates: &[ExchangeRate], year: i32, month: Month) -> Vec<BudgetUsage<'a>> {
+    budgets
+        .iter()
+        .map(|budget| {
+            let spent = expenses
+                .iter()
+                .filter(|expense| expense.get_date().year() == year && expense.get_date().month() == month)
+                .filter(|expense| budget.covers(expense.get_category(), categories))
+                .map(|expense| (expense.get_price(), expense.get_date()));
+
+            BudgetUsage {
+                budget,
+                spent: total_in(rates, spent, budget.budget_limit.currency())
+            }
+        }).collect()
+}
+
+//Budgets which are exceeded in the month of the date and include the category
+pub fn exceeded_by<'a>(budgets: &'a [Budget], expenses: &[Expense], categories: &[Category], rates: &[ExchangeRate], category_id: i32, date: Date) -> Vec<BudgetUsage<'a>> {
+    month_usage(budgets, expenses, categories, rates, date.year(), date.month())
+        .into_iter()
+        .filter(|usage| usage.is_exceeded() && usage.budget.covers(category_id, categories))
+        .collect()
+}
+
+pub fn current_month() -> (i32, Month) {
+    let today = today();
+    (today.year(), today.month())
+}
+
+pub fn shift_month((year, month): (i32, Month), forward: bool) -> (i32, Month) {
+    match (forward, month) {
+        (true, Month::December) => (year + 1, Month::January),
+        (false, Month::January) => (year - 1, Month::December),
+        (true, month) => (year, month.next()),
+        (false, month) => (year, month.previous())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::category::CategoryKind;
+    use crate::exchange::ConversionError;
+    use crate::money::Currency;
+    use crate::pages::notes_page::NoteDraft;
+
+    fn rub(minor: i64) -> Money {
+        Money::from_minor(minor, Currency::RUB)
+    }
+
+    fn date(month: Month, day: u8) -> Date {
+        Date::from_calendar_date(2024, month, day).unwrap()
+    }
+
+    fn category(id: i32, parent: Option<i32>) -> Category {
+        Category::new(id, None, String::new(), CategoryKind::Expense, String::new(), String::new(), parent)
+    }
+
+    fn expense(expense_id: i32, category: i32, price: Money, date: Date) -> Expense {
+        Expense::new(expense_id, 1, NoteDraft {
+            name: String::new(),
+            price,
+            category,
+            account: None,
+            job: None,
+            date
+        })
+    }
+
+    //Category 2 is a subcategory of 1, category 3 is on its own
+    fn categories() -> Vec<Category> {
+        vec![category(1, None), category(2, Some(1)), category(3, None)]
+    }
+
+    #[test]
+    fn budget_covers_subcategories() {
+        let budget = Budget::new(1, 1, rub(1_000));
+        let categories = categories();
+
+        assert!(budget.covers(1, &categories));
+        assert!(budget.covers(2, &categories));
+        assert!(!budget.covers(3, &categories));
+        assert!(!Budget::new(2, 2, rub(1_000)).covers(1, &categories));
+    }
+
+    #[test]
+    fn month_usage_counts_the_month_only() {
+        let budgets = [Budget::new(1, 1, rub(1_000)), Budget::new(2, 3, rub(1_000))];
+        let expenses = [
+            expense(1, 1, rub(600), date(Month::March, 1)),
+            expense(2, 2, rub(500), date(Month::March, 31)),
+            expense(3, 1, rub(900), date(Month::April, 1)),
+            expense(4, 3, rub(200), date(Month::March, 15))
+        ];
+
+        let usage = month_usage(&budgets, &expenses, &categories(), &[], 2024, Month::March);
+
+        assert_eq!(usage.iter().map(|usage| usage.spent.clone()).collect::<Vec<_>>(), [Ok(rub(1_100)), Ok(rub(200))]);
+        assert!(usage[0].is_exceeded());
+        assert_eq!(usage[1].percent(), 20.0);
+
+        let exceeded = exceeded_by(&budgets, &expenses, &categories(), &[], 2, date(Month::March, 5));
+        assert_eq!(exceeded.iter().map(|usage| usage.budget.get_id()).collect::<Vec<i32>>(), [1]);
+        assert!(exceeded_by(&budgets, &expenses, &categories(), &[], 3, date(Month::March, 5)).is_empty());
+    }
+
+    #[test]
+    fn usage_without_rate_or_limit() {
+        let budget = Budget::new(1, 1, rub(0));
+        let foreign = expense(1, 1, Money::from_minor(100, Currency::USD), date(Month::March, 1));
+
+        let free = BudgetUsage {budget: &budget, spent: Ok(rub(500))};
+        let unknown = BudgetUsage {budget: &budget, spent: Err(ConversionError::NoRate(Currency::USD, Currency::RUB, date(Month::March, 1)))};
+
+        assert_eq!(free.percent(), 0.0);
+        assert!(free.is_exceeded());
+        assert!(!unknown.is_exceeded());
+        assert_eq!(month_usage(std::slice::from_ref(&budget), &[foreign], &categories(), &[], 2024, Month::March)[0].spent, unknown.spent);
+    }
+
+    #[test]
+    fn shift_month_across_years() {
+        assert_eq!(shift_month((2024, Month::December), true), (2025, Month::January));
+        assert_eq!(shift_month((2024, Month::January), false), (2023, Month::December));
+        assert_eq!(shift_month((2024, Month::June), true), (2024, Month::July));
+        assert_eq!(shift_month((2024, Month::June), false), (2024, Month::May));
+    }
+}
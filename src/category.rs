@@ -0,0 +1,129 @@
+use std::fmt::{Display, Formatter};
+use sqlx::FromRow;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CategoryKind {
+    Income,
+    Expense
+}
+
+impl CategoryKind {
+    pub const ALL: [CategoryKind; 2] = [CategoryKind::Income, CategoryKind::Expense];
+
+    //Value stored in CATEGORY_KIND column
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CategoryKind::Income => "INCOME",
+            CategoryKind::Expense => "EXPENSE"
+        }
+    }
+}
+
+impl TryFrom<String> for CategoryKind {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        match value.as_str() {
+            "INCOME" => Ok(CategoryKind::Income),
+            "EXPENSE" => Ok(CategoryKind::Expense),
+            other => Err(format!("unknown category kind: {other}"))
+        }
+    }
+}
+
+impl Display for CategoryKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CategoryKind::Income => write!(f, "Доход"),
+            CategoryKind::Expense => write!(f, "Расход")
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, FromRow)]
+pub struct Category {
+    category_id: i32,
+    //None for categories shared between all users
+    category_creator: Option<i32>,
+    category_name: String,
+    #[sqlx(try_from = "String")]
+    category_kind: CategoryKind,
+    category_color: String,
+    category_icon: String,
+    category_parent: Option<i32>
+}
+
+impl Category {
+    pub fn new(
+        category_id: i32,
+        category_creator: Option<i32>,
+        category_name: String,
+        category_kind: CategoryKind,
+        category_color: String,
+        category_icon: String,
+        category_parent: Option<i32>
+    ) -> Self {
+        Self {
+            category_id,
+            category_creator,
+            category_name,
+            category_kind,
+            category_color,
+            category_icon,
+            category_parent
+        }
+    }
+
+    pub fn get_id(&self) -> i32 {
+        self.category_id
+    }
+
+    pub fn get_name(&self) -> &str {
+        &self.category_name
+    }
+
+    pub fn get_kind(&self) -> CategoryKind {
+        self.category_kind
+    }
+
+    pub fn get_icon(&self) -> &str {
+        &self.category_icon
+    }
+
+    pub fn get_parent(&self) -> Option<i32> {
+        self.category_parent
+    }
+
+    pub fn is_shared(&self) -> bool {
+        self.category_creator.is_none()
+    }
+
+    //Falls back to the app accent color if the stored value is broken
+    pub fn get_rgb(&self) -> (u8, u8, u8) {
+        parse_color(&self.category_color).unwrap_or((189, 147, 249))
+    }
+}
+
+impl Display for Category {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self.category_icon.is_empty() {
+            true => write!(f, "{}", self.category_name),
+            false => write!(f, "{} {}", self.category_icon, self.category_name)
+        }
+    }
+}
+
+//Expects color in format #RRGGBB
+pub fn parse_color(input: &str) -> Option<(u8, u8, u8)> {
+    let hex = input.trim().strip_prefix('#')?;
+
+    if hex.len() != 6 || !hex.is_ascii() {
+        return None
+    }
+
+    let red = u8::from_str_radix(&hex[0..2], 16).ok()?;
+    let green = u8::from_str_radix(&hex[2..4], 16).ok()?;
+    let blue = u8::from_str_radix(&hex[4..6], 16).ok()?;
+
+    Some((red, green, blue))
+}
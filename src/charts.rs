@@ -0,0 +1,481 @@
+use std::collections::BTreeMap;
+use std::f32::consts::{FRAC_PI_2, TAU};
+use iced::{alignment, mouse, Color, Point, Rectangle, Renderer, Size, Theme, Vector};
+use iced::widget::canvas::{self, Frame, Geometry, Path, Stroke, Text};
+use rust_decimal::prelude::ToPrimitive;
+use time::{Date, Month};
+use crate::account::Account;
+use crate::allocation::period_bounds;
+use crate::budget::shift_month;
+use crate::category::{Category, CategoryKind};
+use crate::exchange::{ConversionError, ExchangeRate, total_in};
+use crate::expense::Expense;
+use crate::forecast::balance_on;
+use crate::income::Income;
+use crate::money::{Currency, Money};
+
+//Months shown on the dashboard, the current one included
+pub const DASHBOARD_MONTHS: u32 = 12;
+
+const LABEL_SIZE: f32 = 12.0;
+const TOOLTIP_LINE: f32 = 18.0;
+const TOOLTIP_PADDING: f32 = 8.0;
+//Canvas can't measure text, so tooltips are sized by the number of characters
+const CHAR_WIDTH: f32 = 7.5;
+//Room for the axis labels on the left and the month labels below
+const AXIS_WIDTH: f32 = 70.0;
+const AXIS_HEIGHT: f32 = 24.0;
+
+//Everything the charts are calculated from
+pub struct ChartInputs<'a> {
+    pub accounts: &'a [Account],
+    pub incomes: &'a [Income],
+    pub expenses: &'a [Expense],
+    pub categories: &'a [Category],
+    pub rates: &'a [ExchangeRate]
+}
+
+//Part of a pie or of a stacked bar
+#[derive(Clone, Debug)]
+pub struct Segment {
+    pub label: String,
+    pub amount: Money,
+    pub color: Color
+}
+
+#[derive(Clone, Debug)]
+pub struct MonthBars {
+    pub label: String,
+    pub incomes: Vec<Segment>,
+    pub expenses: Vec<Segment>
+}
+
+#[derive(Clone, Debug)]
+pub struct BalancePoint {
+    pub label: String,
+    //at the end of the month, or of today for the current month
+    pub balance: Money
+}
+
+//Last `count` months up to the one of today, oldest first
+pub fn recent_months(today: Date, count: u32) -> Vec<(i32, Month)> {
+    let mut month = (today.year(), today.month());
+    let mut months = vec![month];
+
+    for _ in 1..count {
+        month = shift_month(month, false);
+        months.push(month);
+    }
+
+    months.reverse();
+    months
+}
+
+pub fn month_label((year, month): (i32, Month)) -> String {
+    format!("{:02}.{}", u8::from(month), year)
+}
+
+//Incomes or expenses of the kind dated within the range, grouped by top-level categories
+//(subcategories are added to their parent). Biggest first, categories which add up to zero or less are left out
+pub fn by_category(inputs: &ChartInputs<'_>, kind: CategoryKind, from: Date, to: Date, currency: Currency) -> Result<Vec<Segment>, ConversionError> {
+    let notes = match kind {
+        CategoryKind::Income => inputs.incomes
+            .iter()
+            .map(|income| (income.get_category(), income.get_price(), income.get_date()))
+            .collect::<Vec<(i32, Money, Date)>>(),
+        CategoryKind::Expense => inputs.expenses
+            .iter()
+            .map(|expense| (expense.get_category(), expense.get_price(), expense.get_date()))
+            .collect()
+    };
+
+    let top_level = |category_id: i32| inputs.categories
+        .iter()
+        .find(|category| category.get_id() == category_id)
+        .and_then(|category| category.get_parent())
+        .unwrap_or(category_id);
+
+    let mut groups = BTreeMap::<i32, Vec<(Money, Date)>>::new();
+    for (category_id, price, date) in notes.into_iter().filter(|(_, _, date)| *date >= from && *date <= to) {
+        groups.entry(top_level(category_id)).or_default().push((price, date));
+    }
+
+    let mut segments = Vec::with_capacity(groups.len());
+    for (category_id, amounts) in groups {
+        let amount = total_in(inputs.rates, amounts, currency)?;
+
+        if amount.is_negative() || amount.is_zero() {
+            continue
+        }
+
+        let category = inputs.categories.iter().find(|category| category.get_id() == category_id);
+        let (r, g, b) = category.map_or((189, 147, 249), |category| category.get_rgb());
+
+        segments.push(Segment {
+            label: category.map_or_else(|| "Без категории".to_string(), |category| category.get_name().to_string()),
+            amount,
+            color: Color::from_rgb8(r, g, b)
+        });
+    }
+
+    segments.sort_by_key(|segment| std::cmp::Reverse(segment.amount.minor()));
+    Ok(segments)
+}
+
+pub fn monthly_bars(inputs: &ChartInputs<'_>, months: &[(i32, Month)], currency: Currency) -> Result<Vec<MonthBars>, ConversionError> {
+    months
+        .iter()
+        .map(|month| {
+            let (from, to) = period_bounds(*month);
+
+            Ok(MonthBars {
+                label: month_label(*month),
+                incomes: by_category(inputs, CategoryKind::Income, from, to, currency)?,
+                expenses: by_category(inputs, CategoryKind::Expense, from, to, currency)?
+            })
+        })
+        .collect()
+}
+
+//Balance of the wallets at the end of every month, the current month ends today
+pub fn balance_points(inputs: &ChartInputs<'_>, months: &[(i32, Month)], today: Date, currency: Currency) -> Result<Vec<BalancePoint>, ConversionError> {
+    months
+        .iter()
+        .map(|month| {
+            let date = period_bounds(*month).1.min(today);
+
+            Ok(BalancePoint {
+                label: month_label(*month),
+                balance: balance_on(inputs.accounts, inputs.incomes, inputs.expenses, inputs.rates, date, currency)?
+            })
+        })
+        .collect()
+}
+
+//Donut with a slice for every segment, the hovered one sticks out and gets a tooltip
+pub struct PieChart {
+    pub segments: Vec<Segment>,
+    pub currency: Currency
+}
+
+impl<Message> canvas::Program<Message> for PieChart {
+    type State = ();
+
+    fn draw(&self, _state: &Self::State, renderer: &Renderer, _theme: &Theme, bounds: Rectangle, cursor: mouse::Cursor) -> Vec<Geometry> {
+        let mut frame = Frame::new(renderer, bounds.size());
+        let center = frame.center();
+        let outer = (frame.width().min(frame.height()) / 2.0 - 12.0).max(10.0);
+        let inner = outer * 0.55;
+
+        let total = self.segments.iter().map(|segment| amount_of(segment.amount)).sum::<f32>();
+
+        if total <= 0.0 {
+            frame.stroke(&Path::circle(center, outer), Stroke::default().with_color(muted()).with_width(2.0));
+            frame.fill_text(centered("Нет данных", center, muted(), 16.0));
+            return vec![frame.into_geometry()]
+        }
+
+        //Angle of the cursor, clockwise from the top
+        let hovered_angle = cursor.position_in(bounds).and_then(|position| {
+            let offset = position - center;
+            let distance = (offset.x * offset.x + offset.y * offset.y).sqrt();
+
+            (distance >= inner && distance <= outer + 6.0).then(|| (offset.y.atan2(offset.x) + FRAC_PI_2).rem_euclid(TAU))
+        });
+
+        let mut start = 0.0;
+        let mut hovered = None;
+
+        for segment in &self.segments {
+            let share = amount_of(segment.amount) / total;
+            let end = start + share * TAU;
+            let is_hovered = matches!(hovered_angle, Some(angle) if angle >= start && angle < end);
+            let grow = if is_hovered {6.0} else {0.0};
+
+            frame.fill(&annulus(center, inner, outer + grow, start - FRAC_PI_2, end - FRAC_PI_2), segment.color);
+
+            if is_hovered {
+                hovered = Some((segment, share));
+            }
+
+            start = end;
+        }
+
+        frame.fill_text(centered(&sum_of(&self.segments, self.currency), center, Color::WHITE, 16.0));
+
+        if let (Some((segment, share)), Some(position)) = (hovered, cursor.position_in(bounds)) {
+            tooltip(&mut frame, position, &[segment.label.clone(), format!("{} ({:.1}%)", segment.amount, share * 100.0)]);
+        }
+
+        vec![frame.into_geometry()]
+    }
+}
+
+//Incomes and expenses of every month side by side, both stacked by categories
+pub struct BarChart {
+    pub months: Vec<MonthBars>,
+    pub currency: Currency
+}
+
+impl<Message> canvas::Program<Message> for BarChart {
+    type State = ();
+
+    fn draw(&self, _state: &Self::State, renderer: &Renderer, _theme: &Theme, bounds: Rectangle, cursor: mouse::Cursor) -> Vec<Geometry> {
+        let mut frame = Frame::new(renderer, bounds.size());
+        let plot = plot_area(&frame);
+
+        let bar_total = |segments: &[Segment]| segments.iter().map(|segment| amount_of(segment.amount)).sum::<f32>();
+        let max = self.months
+            .iter()
+            .map(|month| bar_total(&month.incomes).max(bar_total(&month.expenses)))
+            .fold(0.0, f32::max);
+
+        if self.months.is_empty() || max <= 0.0 {
+            frame.fill_text(centered("Нет данных", frame.center(), muted(), 16.0));
+            return vec![frame.into_geometry()]
+        }
+
+        axis(&mut frame, plot, 0.0, max);
+
+        let position = cursor.position_in(bounds);
+        let group_width = plot.width / self.months.len() as f32;
+        let bar_width = group_width * 0.35;
+        let mut hovered = None;
+
+        for (index, month) in self.months.iter().enumerate() {
+            let group_x = plot.x + group_width * index as f32;
+            let bars = [
+                ("Доходы", &month.incomes, group_x + group_width * 0.12),
+                ("Расходы", &month.expenses, group_x + group_width * 0.53)
+            ];
+
+            for (name, segments, x) in bars {
+                let mut bottom = plot.y + plot.height;
+
+                for segment in segments {
+                    let height = amount_of(segment.amount) / max * plot.height;
+                    let top_left = Point::new(x, bottom - height);
+
+                    frame.fill_rectangle(top_left, Size::new(bar_width, height), segment.color);
+
+                    if matches!(position, Some(position) if Rectangle::new(top_left, Size::new(bar_width, height)).contains(position)) {
+                        hovered = Some((month, name, segments, segment));
+                    }
+
+                    bottom -= height;
+                }
+            }
+
+            frame.fill_text(Text {
+                content: month.label.clone(),
+                position: Point::new(group_x + group_width / 2.0, plot.y + plot.height + 6.0),
+                color: muted(),
+                size: LABEL_SIZE.into(),
+                horizontal_alignment: alignment::Horizontal::Center,
+                ..Text::default()
+            });
+        }
+
+        if let (Some((month, name, segments, segment)), Some(position)) = (hovered, position) {
+            let total = sum_of(segments, self.currency);
+
+            tooltip(&mut frame, position, &[
+                month.label.clone(),
+                format!("{name}: {total}"),
+                format!("{}: {}", segment.label, segment.amount)
+            ]);
+        }
+
+        vec![frame.into_geometry()]
+    }
+}
+
+//Balance at the end of every month, the point nearest to the cursor gets a tooltip
+pub struct LineChart {
+    pub points: Vec<BalancePoint>
+}
+
+impl<Message> canvas::Program<Message> for LineChart {
+    type State = ();
+
+    fn draw(&self, _state: &Self::State, renderer: &Renderer, _theme: &Theme, bounds: Rectangle, cursor: mouse::Cursor) -> Vec<Geometry> {
+        let mut frame = Frame::new(renderer, bounds.size());
+        let plot = plot_area(&frame);
+
+        if self.points.is_empty() {
+            frame.fill_text(centered("Нет данных", frame.center(), muted(), 16.0));
+            return vec![frame.into_geometry()]
+        }
+
+        let values = self.points.iter().map(|point| amount_of(point.balance)).collect::<Vec<f32>>();
+        //Zero is always on the chart, so it's seen where the balance goes negative
+        let min = values.iter().copied().fold(0.0, f32::min);
+        let mut max = values.iter().copied().fold(0.0, f32::max);
+        if max <= min {
+            max = min + 1.0;
+        }
+
+        axis(&mut frame, plot, min, max);
+
+        let step = plot.width / self.points.len() as f32;
+        let to_point = |index: usize, value: f32| Point::new(
+            plot.x + step * (index as f32 + 0.5),
+            plot.y + plot.height - (value - min) / (max - min) * plot.height
+        );
+
+        if min < 0.0 {
+            let zero = to_point(0, 0.0).y;
+            frame.stroke(
+                &Path::line(Point::new(plot.x, zero), Point::new(plot.x + plot.width, zero)),
+                Stroke::default().with_color(negative()).with_width(1.0)
+            );
+        }
+
+        let line = Path::new(|builder| {
+            for (index, value) in values.iter().enumerate() {
+                match index {
+                    0 => builder.move_to(to_point(index, *value)),
+                    _ => builder.line_to(to_point(index, *value))
+                }
+            }
+        });
+        frame.stroke(&line, Stroke::default().with_color(accent()).with_width(2.0));
+
+        let hovered = cursor.position_in(bounds)
+            .filter(|position| plot.contains(*position))
+            .map(|position| (((position.x - plot.x) / step) as usize).min(self.points.len() - 1));
+
+        for (index, (point, value)) in self.points.iter().zip(&values).enumerate() {
+            let color = if point.balance.is_negative() {negative()} else {accent()};
+            let radius = if hovered == Some(index) {5.0} else {3.0};
+
+            frame.fill(&Path::circle(to_point(index, *value), radius), color);
+            frame.fill_text(Text {
+                content: point.label.clone(),
+                position: Point::new(to_point(index, *value).x, plot.y + plot.height + 6.0),
+                color: muted(),
+                size: LABEL_SIZE.into(),
+                horizontal_alignment: alignment::Horizontal::Center,
+                ..Text::default()
+            });
+        }
+
+        if let Some(index) = hovered {
+            let point = &self.points[index];
+            tooltip(&mut frame, to_point(index, values[index]), &[point.label.clone(), point.balance.to_string()]);
+        }
+
+        vec![frame.into_geometry()]
+    }
+}
+
+fn amount_of(amount: Money) -> f32 {
+    amount.to_decimal().to_f32().unwrap_or(0.0)
+}
+
+fn sum_of(segments: &[Segment], currency: Currency) -> String {
+    Money::from_decimal(segments.iter().map(|segment| segment.amount.to_decimal()).sum(), currency)
+        .map_or_else(|err| err.to_string(), |sum| sum.to_string())
+}
+
+fn plot_area(frame: &Frame) -> Rectangle {
+    Rectangle::new(
+        Point::new(AXIS_WIDTH, 10.0),
+        Size::new((frame.width() - AXIS_WIDTH - 10.0).max(1.0), (frame.height() - AXIS_HEIGHT - 10.0).max(1.0))
+    )
+}
+
+//Grid lines with values at the bottom, the middle and the top of the plot
+fn axis(frame: &mut Frame, plot: Rectangle, min: f32, max: f32) {
+    for step in 0..=2 {
+        let share = step as f32 / 2.0;
+        let y = plot.y + plot.height - share * plot.height;
+
+        frame.stroke(
+            &Path::line(Point::new(plot.x, y), Point::new(plot.x + plot.width, y)),
+            Stroke::default().with_color(Color {a: 0.2, ..muted()}).with_width(1.0)
+        );
+        frame.fill_text(Text {
+            content: compact(min + (max - min) * share),
+            position: Point::new(plot.x - 6.0, y),
+            color: muted(),
+            size: LABEL_SIZE.into(),
+            horizontal_alignment: alignment::Horizontal::Right,
+            vertical_alignment: alignment::Vertical::Center,
+            ..Text::default()
+        });
+    }
+}
+
+fn compact(value: f32) -> String {
+    match value.abs() {
+        abs if abs >= 1_000_000.0 => format!("{:.1} млн", value / 1_000_000.0),
+        abs if abs >= 1_000.0 => format!("{:.0} тыс.", value / 1_000.0),
+        _ => format!("{value:.0}")
+    }
+}
+
+//Ring segment between the angles, polygon is used because every arc of a path starts a new subpath
+fn annulus(center: Point, inner: f32, outer: f32, start: f32, end: f32) -> Path {
+    let steps = (((end - start) / 0.05).ceil() as usize).max(2);
+    let at = |radius: f32, angle: f32| center + Vector::new(radius * angle.cos(), radius * angle.sin());
+
+    Path::new(|builder| {
+        builder.move_to(at(outer, start));
+        for step in 1..=steps {
+            builder.line_to(at(outer, start + (end - start) * step as f32 / steps as f32));
+        }
+        for step in (0..=steps).rev() {
+            builder.line_to(at(inner, start + (end - start) * step as f32 / steps as f32));
+        }
+        builder.close();
+    })
+}
+
+//Box with the lines next to the position, kept inside the frame
+fn tooltip(frame: &mut Frame, position: Point, lines: &[String]) {
+    let width = lines.iter().map(|line| line.chars().count()).max().unwrap_or(0) as f32 * CHAR_WIDTH + TOOLTIP_PADDING * 2.0;
+    let height = lines.len() as f32 * TOOLTIP_LINE + TOOLTIP_PADDING * 2.0;
+
+    let x = match position.x + 12.0 + width > frame.width() {
+        true => (position.x - 12.0 - width).max(0.0),
+        false => position.x + 12.0
+    };
+    let y = (position.y + 12.0).min(frame.height() - height).max(0.0);
+
+    frame.fill_rectangle(Point::new(x, y), Size::new(width, height), Color::from_rgba8(40, 42, 54, 0.95));
+
+    for (index, line) in lines.iter().enumerate() {
+        frame.fill_text(Text {
+            content: line.clone(),
+            position: Point::new(x + TOOLTIP_PADDING, y + TOOLTIP_PADDING + TOOLTIP_LINE * index as f32),
+            color: Color::WHITE,
+            size: 14.0.into(),
+            ..Text::default()
+        });
+    }
+}
+
+fn centered(content: &str, position: Point, color: Color, size: f32) -> Text {
+    Text {
+        content: content.to_string(),
+        position,
+        color,
+        size: size.into(),
+        horizontal_alignment: alignment::Horizontal::Center,
+        vertical_alignment: alignment::Vertical::Center,
+        ..Text::default()
+    }
+}
+
+fn accent() -> Color {
+    Color::from_rgb8(189, 147, 249)
+}
+
+fn negative() -> Color {
+    Color::from_rgba8(206, 44, 44, 0.8)
+}
+
+fn muted() -> Color {
+    Color::from_rgb8(160, 160, 170)
+}
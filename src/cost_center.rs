@@ -0,0 +1,406 @@
+use std::fmt::{Display, Formatter};
+use rust_decimal::{Decimal, RoundingStrategy};
+use sqlx::{FromRow, Row};
+use sqlx::mysql::MySqlRow;
+use time::Date;
+use crate::allocation::largest_remainder;
+use crate::exchange::{ConversionError, ExchangeRate, convert};
+use crate::expense::Expense;
+use crate::money::{Currency, Money, MoneyError};
+
+//Project or department which spending is attributed to
+#[derive(Clone, Debug, PartialEq, FromRow)]
+pub struct CostCenter {
+    center_id: i32,
+    center_name: String
+}
+
+impl CostCenter {
+    pub fn new(center_id: i32, center_name: String) -> Self {
+        Self {
+            center_id,
+            center_name
+        }
+    }
+
+    pub fn get_id(&self) -> i32 {
+        self.center_id
+    }
+
+    pub fn get_name(&self) -> &str {
+        &self.center_name
+    }
+}
+
+//Shown in pick lists
+impl Display for CostCenter {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.center_name)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Share {
+    //percents of the expense, from 0 to 100
+    Percent(Decimal),
+    //in the currency of the expense
+    Amount(Money)
+}
+
+impl Display for Share {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Share::Percent(percent) => write!(f, "{}%", percent.normalize()),
+            Share::Amount(amount) => write!(f, "{amount}")
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ShareKind {
+    Percent,
+    Amount
+}
+
+impl ShareKind {
+    pub const ALL: [ShareKind; 2] = [ShareKind::Percent, ShareKind::Amount];
+}
+
+impl Display for ShareKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ShareKind::Percent => write!(f, "Процент"),
+            ShareKind::Amount => write!(f, "Сумма")
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct ExpenseSplit {
+    split_id: i32,
+    split_expense: i32,
+    split_center: i32,
+    split_share: Share
+}
+
+impl ExpenseSplit {
+    //Splits are saved and loaded only through the database
+    #[cfg(test)]
+    pub fn new(split_id: i32, draft: SplitDraft) -> Self {
+        Self {
+            split_id,
+            split_expense: draft.expense,
+            split_center: draft.center,
+            split_share: draft.share
+        }
+    }
+
+    pub fn get_id(&self) -> i32 {
+        self.split_id
+    }
+
+    pub fn get_expense(&self) -> i32 {
+        self.split_expense
+    }
+
+    pub fn get_center(&self) -> i32 {
+        self.split_center
+    }
+
+    pub fn get_share(&self) -> Share {
+        self.split_share
+    }
+}
+
+//Queries alias the share to `percent` or `amount`/`currency`, only one of them is set
+impl<'r> FromRow<'r, MySqlRow> for ExpenseSplit {
+    fn from_row(row: &'r MySqlRow) -> Result<Self, sqlx::Error> {
+        let percent: Option<Decimal> = row.try_get("percent")?;
+        let amount: Option<Decimal> = row.try_get("amount")?;
+        let currency: Option<Currency> = row.try_get("currency")?;
+
+        let split_share = match (percent, amount, currency) {
+            (Some(percent), None, _) => Share::Percent(percent),
+            (None, Some(amount), Some(currency)) => Share::Amount(Money::from_decimal(amount, currency).map_err(|err| sqlx::Error::Decode(Box::new(err)))?),
+            _ => return Err(sqlx::Error::Decode("split must have either a percent or an amount".into()))
+        };
+
+        Ok(ExpenseSplit {
+            split_id: row.try_get("split_id")?,
+            split_expense: row.try_get("split_expense")?,
+            split_center: row.try_get("split_center")?,
+            split_share
+        })
+    }
+}
+
+pub struct SplitDraft {
+    pub expense: i32,
+    pub center: i32,
+    pub share: Share
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum SplitError {
+    //more than the whole expense is attributed, by the given amount
+    OverAllocated(Money),
+    Conversion(ConversionError)
+}
+
+impl Display for SplitError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SplitError::OverAllocated(excess) => write!(f, "Разнесено больше суммы расхода на {excess}"),
+            SplitError::Conversion(err) => write!(f, "{err}")
+        }
+    }
+}
+
+impl From<MoneyError> for SplitError {
+    fn from(value: MoneyError) -> Self {
+        SplitError::Conversion(ConversionError::Money(value))
+    }
+}
+
+impl From<ConversionError> for SplitError {
+    fn from(value: ConversionError) -> Self {
+        SplitError::Conversion(value)
+    }
+}
+
+//Amounts of the expense attributed to every center it's split on.
+//Percents are rounded together and split by the largest remainder, so they never add up to more than the expense.
+//Fixed amounts are taken with the sign of the expense, so refunds are split the same way.
+pub fn allocate(expense: &Expense, splits: &[ExpenseSplit]) -> Result<Vec<(i32, Money)>, SplitError> {
+    let price = expense.get_price();
+    let own = splits
+        .iter()
+        .filter(|split| split.split_expense == expense.get_id())
+        .collect::<Vec<&ExpenseSplit>>();
+
+    let percents = own
+        .iter()
+        .filter_map(|split| match split.split_share {
+            Share::Percent(percent) => Some((split.split_center, percent)),
+            Share::Amount(_) => None
+        })
+        .collect::<Vec<(i32, Decimal)>>();
+    let mut percent_parts = percent_amounts(price, &percents)?.into_iter();
+
+    let allocations = own
+        .iter()
+        .map(|split| {
+            let amount = match split.split_share {
+                Share::Percent(_) => percent_parts.next().map_or(Money::zero(price.currency()), |(_, amount)| amount),
+                Share::Amount(amount) if price.is_negative() => amount.checked_neg()?,
+                Share::Amount(amount) => amount
+            };
+            Ok((split.split_center, amount))
+        })
+        .collect::<Result<Vec<(i32, Money)>, SplitError>>()?;
+
+    let allocated = Money::sum(allocations.iter().map(|(_, amount)| *amount), price.currency())?;
+
+    if allocated.minor().abs() > price.minor().abs() {
+        return Err(SplitError::OverAllocated(Money::from_minor(allocated.minor().abs() - price.minor().abs(), price.currency())))
+    }
+
+    Ok(allocations)
+}
+
+//Checks that the share still fits into the expense, the split of the same center is replaced by it
+pub fn fits(expense: &Expense, splits: &[ExpenseSplit], center: i32, share: Share) -> Result<(), SplitError> {
+    let mut others = splits
+        .iter()
+        .filter(|split| split.split_expense == expense.get_id() && split.split_center != center)
+        .cloned()
+        .collect::<Vec<ExpenseSplit>>();
+
+    others.push(ExpenseSplit {
+        split_id: 0,
+        split_expense: expense.get_id(),
+        split_center: center,
+        split_share: share
+    });
+
+    allocate(expense, &others).map(|_| ())
+}
+
+//Part of the expense which isn't attributed to any center
+pub fn unallocated(expense: &Expense, splits: &[ExpenseSplit]) -> Result<Money, SplitError> {
+    let price = expense.get_price();
+    let allocated = Money::sum(allocate(expense, splits)?.into_iter().map(|(_, amount)| amount), price.currency())?;
+
+    Ok(price.checked_sub(allocated)?)
+}
+
+//Parts of the price for the percents in the same order, their total is the rounded sum of the percents
+fn percent_amounts(price: Money, percents: &[(i32, Decimal)]) -> Result<Vec<(i32, Money)>, MoneyError> {
+    let total = percents.iter().map(|(_, percent)| *percent).sum::<Decimal>();
+
+    if total <= Decimal::ZERO {
+        return Ok(percents.iter().map(|(center, _)| (*center, Money::zero(price.currency()))).collect())
+    }
+
+    let pooled = price
+        .to_decimal()
+        .checked_mul(total)
+        .ok_or(MoneyError::Overflow)?
+        / Decimal::ONE_HUNDRED;
+
+    largest_remainder(Money::from_decimal(pooled.round_dp_with_strategy(2, RoundingStrategy::MidpointAwayFromZero), price.currency())?, percents)
+}
+
+pub struct CenterTotal {
+    //None for the part of expenses which isn't attributed to any center
+    pub center: Option<i32>,
+    pub total: Money
+}
+
+pub struct CenterReport {
+    pub totals: Vec<CenterTotal>,
+    //expenses left out of the totals because their splits can't be applied, with the reason
+    pub failed: Vec<(i32, SplitError)>
+}
+
+impl CenterReport {
+    //Totals only if every expense got into them, for calculations which can't skip any
+    pub fn complete(self) -> Result<Vec<CenterTotal>, SplitError> {
+        match self.failed.into_iter().next() {
+            Some((_, err)) => Err(err),
+            None => Ok(self.totals)
+        }
+    }
+}
+
+//Totals of expenses dated within the range (both ends included, None means unbounded) per center,
+//converted to the currency with the rates of expense dates. Centers without expenses have zero total.
+//Expense which can't be split or converted is reported on its own instead of failing the whole report.
+pub fn center_totals(centers: &[CostCenter], expenses: &[Expense], splits: &[ExpenseSplit], rates: &[ExchangeRate], from: Option<Date>, to: Option<Date>, currency: Currency) -> Result<CenterReport, SplitError> {
+    let mut totals = centers
+        .iter()
+        .map(|center| CenterTotal {center: Some(center.center_id), total: Money::zero(currency)})
+        .chain(std::iter::once(CenterTotal {center: None, total: Money::zero(currency)}))
+        .collect::<Vec<CenterTotal>>();
+    let mut failed = Vec::new();
+
+    let in_range = |date: Date| !matches!(from, Some(from) if date < from) && !matches!(to, Some(to) if date > to);
+
+    let parts_of = |expense: &Expense| -> Result<Vec<(Option<i32>, Money)>, SplitError> {
+        let date = expense.get_date();
+        let rest = unallocated(expense, splits)?;
+
+        allocate(expense, splits)?
+            .into_iter()
+            .map(|(center, amount)| (Some(center), amount))
+            .chain(std::iter::once((None, rest)))
+            .map(|(center, amount)| Ok((center, convert(rates, amount, currency, date)?)))
+            .collect()
+    };
+
+    for expense in expenses.iter().filter(|expense| in_range(expense.get_date())) {
+        let parts = match parts_of(expense) {
+            Ok(parts) => parts,
+            Err(err) => {
+                failed.push((expense.get_id(), err));
+                continue
+            }
+        };
+
+        for (center, amount) in parts {
+            if let Some(total) = totals.iter_mut().find(|total| total.center == center) {
+                total.total = total.total.checked_add(amount)?;
+            }
+        }
+    }
+
+    Ok(CenterReport {totals, failed})
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use time::Month;
+    use crate::pages::notes_page::NoteDraft;
+
+    fn rub(minor: i64) -> Money {
+        Money::from_minor(minor, Currency::RUB)
+    }
+
+    fn date(day: u8) -> Date {
+        Date::from_calendar_date(2024, Month::March, day).unwrap()
+    }
+
+    fn expense(expense_id: i32, minor: i64, day: u8) -> Expense {
+        Expense::new(expense_id, 1, NoteDraft {
+            name: String::new(),
+            price: rub(minor),
+            category: 1,
+            account: None,
+            job: None,
+            date: date(day)
+        })
+    }
+
+    fn split(expense: i32, center: i32, share: Share) -> ExpenseSplit {
+        ExpenseSplit::new(0, SplitDraft {expense, center, share})
+    }
+
+    fn halves(expense: i32) -> Vec<ExpenseSplit> {
+        vec![split(expense, 1, Share::Percent(Decimal::from(50))), split(expense, 2, Share::Percent(Decimal::from(50)))]
+    }
+
+    #[test]
+    fn odd_kopeck_halves_add_up_to_the_price() {
+        assert_eq!(allocate(&expense(1, 1, 1), &halves(1)).unwrap(), [(1, rub(1)), (2, rub(0))]);
+        assert_eq!(allocate(&expense(1, 101, 1), &halves(1)).unwrap(), [(1, rub(51)), (2, rub(50))]);
+        assert_eq!(allocate(&expense(1, -101, 1), &halves(1)).unwrap(), [(1, rub(-51)), (2, rub(-50))]);
+        assert_eq!(unallocated(&expense(1, 101, 1), &halves(1)).unwrap(), rub(0));
+    }
+
+    #[test]
+    fn thirds_add_up_to_the_price() {
+        let splits = [
+            split(1, 1, Share::Percent(Decimal::new(3333, 2))),
+            split(1, 2, Share::Percent(Decimal::new(3333, 2))),
+            split(1, 3, Share::Percent(Decimal::new(3334, 2)))
+        ];
+
+        assert_eq!(allocate(&expense(1, 100, 1), &splits).unwrap(), [(1, rub(33)), (2, rub(33)), (3, rub(34))]);
+        assert_eq!(allocate(&expense(1, 1, 1), &splits).unwrap(), [(1, rub(0)), (2, rub(0)), (3, rub(1))]);
+    }
+
+    #[test]
+    fn fixed_amounts_follow_the_sign_and_can_overflow() {
+        let splits = [split(1, 1, Share::Amount(rub(300))), split(1, 2, Share::Percent(Decimal::from(30))), split(2, 3, Share::Amount(rub(100)))];
+
+        assert_eq!(allocate(&expense(1, -1000, 1), &splits).unwrap(), [(1, rub(-300)), (2, rub(-300))]);
+        assert_eq!(unallocated(&expense(1, 1000, 1), &splits).unwrap(), rub(400));
+        assert_eq!(allocate(&expense(1, 400, 1), &splits), Err(SplitError::OverAllocated(rub(20))));
+    }
+
+    #[test]
+    fn fits_replaces_the_split_of_the_same_center() {
+        let expense = expense(1, 1000, 1);
+        let splits = [split(1, 1, Share::Percent(Decimal::from(60))), split(1, 2, Share::Percent(Decimal::from(40)))];
+
+        assert_eq!(fits(&expense, &splits, 1, Share::Percent(Decimal::from(50))), Ok(()));
+        assert_eq!(fits(&expense, &splits, 1, Share::Amount(rub(600))), Ok(()));
+        assert_eq!(fits(&expense, &splits, 3, Share::Amount(rub(1))), Err(SplitError::OverAllocated(rub(1))));
+    }
+
+    #[test]
+    fn center_totals_report_unsplittable_expenses() {
+        let centers = [CostCenter::new(1, String::from("Цех")), CostCenter::new(2, String::from("Склад"))];
+        let expenses = [expense(1, 101, 1), expense(2, 500, 2), expense(3, 700, 3), expense(4, 900, 20)];
+        let mut splits = halves(1);
+        splits.push(split(2, 2, Share::Amount(rub(600))));
+
+        let report = center_totals(&centers, &expenses, &splits, &[], Some(date(1)), Some(date(10)), Currency::RUB).unwrap();
+        let totals = report.totals.iter().map(|total| (total.center, total.total)).collect::<Vec<(Option<i32>, Money)>>();
+
+        assert_eq!(totals, [(Some(1), rub(51)), (Some(2), rub(50)), (None, rub(700))]);
+        assert_eq!(report.failed, [(2, SplitError::OverAllocated(rub(100)))]);
+        assert_eq!(report.complete().err(), Some(SplitError::OverAllocated(rub(100))));
+    }
+}
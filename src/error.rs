@@ -1,3 +1,4 @@
+use std::fmt::{Display, Formatter};
 use sqlx::Error;
 #[derive(Debug, Clone, PartialEq)]
 pub enum QueryError {
@@ -9,13 +10,21 @@ pub enum QueryError {
     ConnectionProblem,
     WrongType(String),
     ColumnIndexOutOfBounds(usize, usize),
+    HashingProblem,
+    UnbalancedEntry,
+    //depreciation of the month is already posted, so its usage can't change
+    MonthPosted,
+    //issues of the item still need the lot
+    LotIssued,
     OtherProblem,
 }
 
 impl QueryError {
     pub fn match_sqlx_error(err: sqlx::Error) -> Self {
         match err {
-            Error::RowNotFound | Error::ColumnNotFound(_) => QueryError::NoResultFound,
+            Error::RowNotFound => QueryError::NoResultFound,
+            //Column is missing or has another type, it means that the schema differs from the one we expect
+            Error::ColumnNotFound(_) | Error::ColumnDecode {..} => QueryError::DecodingProblem,
             Error::PoolClosed | Error::PoolTimedOut => QueryError::PoolProblem,
             Error::TypeNotFound {type_name} => QueryError::WrongType(type_name),
             Error::ColumnIndexOutOfBounds {index, len} => QueryError::ColumnIndexOutOfBounds(index, len),
@@ -25,4 +34,24 @@ impl QueryError {
             _ => QueryError::OtherProblem
         }
     }
-}
\ No newline at end of file
+}
+
+impl Display for QueryError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            QueryError::NoResultFound => write!(f, "Данные не найдены"),
+            QueryError::WrongQueryFormat => write!(f, "Неверный формат запроса"),
+            QueryError::PoolProblem => write!(f, "Нет свободного соединения с базой данных"),
+            QueryError::DataBaseError => write!(f, "Ошибка базы данных"),
+            QueryError::DecodingProblem => write!(f, "Не удалось прочитать данные из базы"),
+            QueryError::ConnectionProblem => write!(f, "Проблема с подключением к базе данных"),
+            QueryError::WrongType(type_name) => write!(f, "Неизвестный тип данных: {type_name}"),
+            QueryError::ColumnIndexOutOfBounds(index, len) => write!(f, "Столбец {index} вне диапазона (всего {len})"),
+            QueryError::HashingProblem => write!(f, "Не удалось зашифровать пароль"),
+            QueryError::UnbalancedEntry => write!(f, "Проводка не сбалансирована"),
+            QueryError::LotIssued => write!(f, "Партия уже списана, сначала удалите списания"),
+            QueryError::MonthPosted => write!(f, "Месяц уже проведён, выработку за него изменить нельзя"),
+            QueryError::OtherProblem => write!(f, "Неизвестная ошибка")
+        }
+    }
+}
@@ -0,0 +1,304 @@
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+use rust_decimal::{Decimal, RoundingStrategy};
+use sqlx::FromRow;
+use time::{Date, Month};
+use crate::error::QueryError;
+use crate::money::{Currency, Money, MoneyError};
+
+//One unit of rate_from costs rate_value units of rate_to starting from rate_date
+#[derive(Clone, Debug, PartialEq, FromRow)]
+pub struct ExchangeRate {
+    rate_id: i32,
+    rate_from: Currency,
+    rate_to: Currency,
+    rate_value: Decimal,
+    rate_date: Date
+}
+
+impl ExchangeRate {
+    pub fn new(rate_id: i32, rate_from: Currency, rate_to: Currency, rate_value: Decimal, rate_date: Date) -> Self {
+        Self {
+            rate_id,
+            rate_from,
+            rate_to,
+            rate_value,
+            rate_date
+        }
+    }
+
+    pub fn get_id(&self) -> i32 {
+        self.rate_id
+    }
+
+    pub fn get_from(&self) -> Currency {
+        self.rate_from
+    }
+
+    pub fn get_to(&self) -> Currency {
+        self.rate_to
+    }
+
+    pub fn get_value(&self) -> Decimal {
+        self.rate_value
+    }
+
+    pub fn get_date(&self) -> Date {
+        self.rate_date
+    }
+}
+
+pub struct RateDraft {
+    pub from: Currency,
+    pub to: Currency,
+    pub value: Decimal,
+    pub date: Date
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ConversionError {
+    NoRate(Currency, Currency, Date),
+    Money(MoneyError)
+}
+
+impl Display for ConversionError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConversionError::NoRate(from, to, date) => write!(f, "Нет курса {from}→{to} на {:02}.{:02}.{}", date.day(), u8::from(date.month()), date.year()),
+            ConversionError::Money(err) => write!(f, "{err}")
+        }
+    }
+}
+
+impl From<MoneyError> for ConversionError {
+    fn from(value: MoneyError) -> Self {
+        ConversionError::Money(value)
+    }
+}
+
+//Rate valid on the date is the latest one set on that day or before it.
+//Reverse rate is used when only the opposite direction is known or when it was set later,
+//on the same day the direct rate wins.
+pub fn rate_on(rates: &[ExchangeRate], from: Currency, to: Currency, date: Date) -> Option<Decimal> {
+    if from == to {
+        return Some(Decimal::ONE)
+    }
+
+    let latest = |from: Currency, to: Currency| {
+        rates
+            .iter()
+            .filter(|rate| rate.rate_from == from && rate.rate_to == to && rate.rate_date <= date)
+            .max_by_key(|rate| rate.rate_date)
+    };
+
+    let direct = latest(from, to).map(|rate| (rate.rate_date, rate.rate_value));
+    let reverse = latest(to, from)
+        .filter(|rate| !rate.rate_value.is_zero())
+        .and_then(|rate| Some((rate.rate_date, Decimal::ONE.checked_div(rate.rate_value)?)));
+
+    [direct, reverse]
+        .into_iter()
+        .flatten()
+        .reduce(|direct, reverse| if reverse.0 > direct.0 {reverse} else {direct})
+        .map(|(_, value)| value)
+}
+
+pub fn convert(rates: &[ExchangeRate], amount: Money, to: Currency, date: Date) -> Result<Money, ConversionError> {
+    if amount.currency() == to {
+        return Ok(amount)
+    }
+
+    let rate = rate_on(rates, amount.currency(), to, date).ok_or(ConversionError::NoRate(amount.currency(), to, date))?;
+
+    let converted = amount
+        .to_decimal()
+        .checked_mul(rate)
+        .ok_or(ConversionError::Money(MoneyError::Overflow))?
+        .round_dp_with_strategy(2, RoundingStrategy::MidpointAwayFromZero);
+
+    Ok(Money::from_decimal(converted, to)?)
+}
+
+//Every amount is converted with the rate of its own date
+pub fn total_in(rates: &[ExchangeRate], amounts: impl IntoIterator<Item = (Money, Date)>, currency: Currency) -> Result<Money, ConversionError> {
+    amounts
+        .into_iter()
+        .try_fold(Money::zero(currency), |total, (amount, date)| {
+            Ok(total.checked_add(convert(rates, amount, currency, date)?)?)
+        })
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ImportError {
+    CantReadFile(String),
+    WrongLine(usize),
+    Query(QueryError)
+}
+
+impl Display for ImportError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ImportError::CantReadFile(path) => write!(f, "Не удалось прочитать файл {path}"),
+            ImportError::WrongLine(line) => write!(f, "Неверный формат строки {line}"),
+            ImportError::Query(err) => write!(f, "{err}")
+        }
+    }
+}
+
+//Line format: date,from,to,rate (2024-01-31,USD,RUB,89.50)
+//or date;from;to;rate with comma in the rate, as spreadsheets save it in Ru locale.
+//Dates may be written as yyyy-mm-dd or dd.mm.yyyy, a header line is skipped.
+pub fn parse_rates_csv(content: &str) -> Result<Vec<RateDraft>, ImportError> {
+    let mut drafts = Vec::new();
+
+    for (index, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue
+        }
+
+        let (fields, rate): (Vec<&str>, String) = match line.contains(';') {
+            true => {
+                let fields: Vec<&str> = line.split(';').map(str::trim).collect();
+                let rate = fields.get(3).map(|rate| rate.replace(',', ".")).unwrap_or_default();
+                (fields, rate)
+            }
+            false => {
+                let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+                let rate = fields.get(3).map(|rate| rate.to_string()).unwrap_or_default();
+                (fields, rate)
+            }
+        };
+
+        let date = fields.first().and_then(|date| parse_any_date(date));
+
+        if index == 0 && date.is_none() {
+            continue
+        }
+
+        let draft = match (fields.len(), date, fields.get(1).and_then(|code| Currency::from_code(code)), fields.get(2).and_then(|code| Currency::from_code(code)), Decimal::from_str(&rate)) {
+            (4, Some(date), Some(from), Some(to), Ok(value)) if value > Decimal::ZERO && from != to => RateDraft {from, to, value, date},
+            _ => return Err(ImportError::WrongLine(index + 1))
+        };
+
+        drafts.push(draft);
+    }
+
+    Ok(drafts)
+}
+
+fn parse_any_date(input: &str) -> Option<Date> {
+    let dashed: Vec<&str> = input.split('-').collect();
+    let dotted: Vec<&str> = input.split('.').collect();
+
+    let (year, month, day) = match (&dashed[..], &dotted[..]) {
+        ([year, month, day], _) => (*year, *month, *day),
+        (_, [day, month, year]) => (*year, *month, *day),
+        _ => return None
+    };
+
+    Date::from_calendar_date(
+        year.parse().ok()?,
+        Month::try_from(month.parse::<u8>().ok()?).ok()?,
+        day.parse().ok()?
+    ).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(month: Month, day: u8) -> Date {
+        Date::from_calendar_date(2024, month, day).unwrap()
+    }
+
+    fn rate(from: Currency, to: Currency, value: Decimal, date: Date) -> ExchangeRate {
+        ExchangeRate::new(0, from, to, value, date)
+    }
+
+    fn usd(minor: i64) -> Money {
+        Money::from_minor(minor, Currency::USD)
+    }
+
+    fn rub(minor: i64) -> Money {
+        Money::from_minor(minor, Currency::RUB)
+    }
+
+    #[test]
+    fn latest_rate_on_or_before_the_date() {
+        let rates = [
+            rate(Currency::USD, Currency::RUB, Decimal::from(90), date(Month::January, 10)),
+            rate(Currency::USD, Currency::RUB, Decimal::from(92), date(Month::February, 1)),
+            rate(Currency::USD, Currency::RUB, Decimal::from(95), date(Month::March, 1))
+        ];
+
+        assert_eq!(rate_on(&rates, Currency::USD, Currency::RUB, date(Month::January, 9)), None);
+        assert_eq!(rate_on(&rates, Currency::USD, Currency::RUB, date(Month::January, 10)), Some(Decimal::from(90)));
+        assert_eq!(rate_on(&rates, Currency::USD, Currency::RUB, date(Month::February, 20)), Some(Decimal::from(92)));
+        assert_eq!(rate_on(&rates, Currency::RUB, Currency::RUB, date(Month::January, 1)), Some(Decimal::ONE));
+    }
+
+    #[test]
+    fn reverse_rate_is_inverted() {
+        let rates = [rate(Currency::RUB, Currency::USD, Decimal::new(1, 2), date(Month::January, 10))];
+        let zero = [rate(Currency::RUB, Currency::USD, Decimal::ZERO, date(Month::January, 10))];
+
+        assert_eq!(rate_on(&rates, Currency::USD, Currency::RUB, date(Month::January, 10)), Some(Decimal::from(100)));
+        assert_eq!(rate_on(&zero, Currency::USD, Currency::RUB, date(Month::January, 10)), None);
+    }
+
+    #[test]
+    fn newer_of_direct_and_reverse_rate_wins() {
+        let rates = [
+            rate(Currency::USD, Currency::RUB, Decimal::from(90), date(Month::January, 10)),
+            rate(Currency::RUB, Currency::USD, Decimal::new(1, 2), date(Month::February, 1)),
+            rate(Currency::USD, Currency::RUB, Decimal::from(95), date(Month::March, 1)),
+            rate(Currency::RUB, Currency::USD, Decimal::new(1, 1), date(Month::March, 1))
+        ];
+
+        assert_eq!(rate_on(&rates, Currency::USD, Currency::RUB, date(Month::January, 31)), Some(Decimal::from(90)));
+        assert_eq!(rate_on(&rates, Currency::USD, Currency::RUB, date(Month::February, 1)), Some(Decimal::from(100)));
+        assert_eq!(rate_on(&rates, Currency::USD, Currency::RUB, date(Month::March, 1)), Some(Decimal::from(95)));
+    }
+
+    #[test]
+    fn convert_rounds_half_away_from_zero() {
+        let rates = [rate(Currency::USD, Currency::RUB, Decimal::new(905, 1), date(Month::January, 10))];
+
+        assert_eq!(convert(&rates, usd(1), Currency::RUB, date(Month::January, 10)), Ok(rub(91)));
+        assert_eq!(convert(&rates, usd(-1), Currency::RUB, date(Month::January, 10)), Ok(rub(-91)));
+        assert_eq!(convert(&rates, usd(150), Currency::RUB, date(Month::January, 10)), Ok(rub(13575)));
+        assert_eq!(convert(&rates, rub(5), Currency::RUB, date(Month::January, 1)), Ok(rub(5)));
+    }
+
+    #[test]
+    fn total_in_needs_a_rate_for_every_date() {
+        let rates = [rate(Currency::USD, Currency::RUB, Decimal::from(90), date(Month::February, 1))];
+        let amounts = [(usd(100), date(Month::February, 1)), (rub(50), date(Month::January, 1))];
+
+        assert_eq!(total_in(&rates, amounts, Currency::RUB), Ok(rub(9050)));
+        assert_eq!(
+            total_in(&rates, [(usd(100), date(Month::January, 31))], Currency::RUB),
+            Err(ConversionError::NoRate(Currency::USD, Currency::RUB, date(Month::January, 31)))
+        );
+    }
+
+    #[test]
+    fn parses_both_csv_formats() {
+        let content = "date,from,to,rate\n2024-01-31,USD,RUB,89.50\n\n31.01.2024;EUR;RUB;97,25\n";
+        let drafts = parse_rates_csv(content).unwrap();
+
+        assert_eq!(drafts.len(), 2);
+        assert_eq!((drafts[0].from, drafts[0].to, drafts[0].value, drafts[0].date), (Currency::USD, Currency::RUB, Decimal::new(8950, 2), date(Month::January, 31)));
+        assert_eq!((drafts[1].from, drafts[1].to, drafts[1].value, drafts[1].date), (Currency::EUR, Currency::RUB, Decimal::new(9725, 2), date(Month::January, 31)));
+    }
+
+    #[test]
+    fn wrong_lines_are_numbered() {
+        assert_eq!(parse_rates_csv("2024-01-31,USD,RUB,89.50\n2024-02-30,USD,RUB,90").err(), Some(ImportError::WrongLine(2)));
+        assert_eq!(parse_rates_csv("2024-01-31,USD,USD,1").err(), Some(ImportError::WrongLine(1)));
+        assert_eq!(parse_rates_csv("2024-01-31;USD;RUB;0\n").err(), Some(ImportError::WrongLine(1)));
+        assert_eq!(parse_rates_csv("date;from;to;rate\n31.01.2024;USD;RUB;-1").err(), Some(ImportError::WrongLine(2)));
+        assert_eq!(parse_rates_csv("2024-01-31,USD,RUB").err(), Some(ImportError::WrongLine(1)));
+    }
+}
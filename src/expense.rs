@@ -1,49 +1,173 @@
-use sqlx::mysql::MySqlRow;
-use sqlx::Row;
-use sqlx::types::time::Date;
+use std::collections::BTreeMap;
+use sqlx::FromRow;
+use crate::exchange::{ConversionError, ExchangeRate, total_in};
+use crate::pages::notes_page::NoteDraft;
+use crate::money::{Currency, Money};
+use time::{Date, Month};
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, FromRow)]
 pub struct Expense {
     expenses_id: i32,
     expenses_creator: i32,
     expenses_name: String,
-    expenses_price: u32,
+    //read from `price` and `currency` columns
+    #[sqlx(flatten)]
+    expenses_price: Money,
     expenses_category: i32,
+    //None for notes entered before accounts were added
+    expenses_account: Option<i32>,
+    //order or project the note belongs to
+    expenses_job: Option<i32>,
+    //recurring template the note was created from
+    expenses_template: Option<i32>,
+    //set for depreciation of a fixed asset, it's changed only through the asset
+    expenses_asset: Option<i32>,
     expenses_date: Date
 }
 
 impl Expense {
-    pub fn new(
-        expenses_id: i32,
-        expenses_creator: i32,
-        expenses_name: String,
-        expenses_price: u32,
-        expenses_category: i32,
-        expenses_date: Date
-    ) -> Self {
+    pub fn new(expenses_id: i32, expenses_creator: i32, draft: NoteDraft) -> Self {
         Self {
             expenses_id,
             expenses_creator,
-            expenses_name,
-            expenses_price,
-            expenses_category,
-            expenses_date,
+            expenses_name: draft.name,
+            expenses_price: draft.price,
+            expenses_category: draft.category,
+            expenses_account: draft.account,
+            expenses_job: draft.job,
+            expenses_template: None,
+            expenses_asset: None,
+            expenses_date: draft.date
         }
     }
 
-    pub fn collect_from_query_vec(expenses_rows: Vec<MySqlRow>) -> Vec<Self> {
-        expenses_rows
-            .into_iter()
-            .map(|expense| {
-                Self::new(
-                    expense.get("expense_id"),
-                    expense.get("expense_creator"),
-                    expense.get("expense_name"),
-                    expense.get("expense_price"),
-                    expense.get("expense_category"),
-                    expense.get("expense_date")
-                )
-            }).collect()
+    pub fn get_id(&self) -> i32 {
+        self.expenses_id
+    }
+
+    pub fn get_name(&self) -> &str {
+        &self.expenses_name
+    }
+
+    pub fn get_price(&self) -> Money {
+        self.expenses_price
+    }
+
+    pub fn get_category(&self) -> i32 {
+        self.expenses_category
+    }
+
+    pub fn get_account(&self) -> Option<i32> {
+        self.expenses_account
+    }
+
+    pub fn get_job(&self) -> Option<i32> {
+        self.expenses_job
+    }
+
+    pub fn get_asset(&self) -> Option<i32> {
+        self.expenses_asset
+    }
+
+    pub fn get_template(&self) -> Option<i32> {
+        self.expenses_template
+    }
+
+    pub fn get_date(&self) -> Date {
+        self.expenses_date
+    }
+
+    //Notes created from templates and by depreciation come only from the database
+    #[cfg(test)]
+    pub fn with_source(mut self, template: Option<i32>, asset: Option<i32>) -> Self {
+        self.expenses_template = template;
+        self.expenses_asset = asset;
+        self
+    }
+}
 
+pub struct MonthlyExpenses<'a> {
+    pub year: i32,
+    pub month: Month,
+    pub expenses: Vec<&'a Expense>,
+    pub subtotal: Result<Money, ConversionError>
+}
+
+//Groups expenses by calendar month, newest month first, each group sorted by date.
+//Subtotals are converted to the currency with the rates valid on dates of expenses.
+pub fn group_by_month<'a>(expenses: &'a [Expense], rates: &[ExchangeRate], currency: Currency) -> Vec<MonthlyExpenses<'a>> {
+    let mut groups: BTreeMap<(i32, u8), Vec<&Expense>> = BTreeMap::new();
+
+    for expense in expenses {
+        let date = expense.get_date();
+        groups.entry((date.year(), u8::from(date.month()))).or_default().push(expense);
+    }
+
+    groups
+        .into_iter()
+        .rev()
+        .map(|((year, _), mut group)| {
+            group.sort_by_key(|expense| expense.get_date());
+
+            MonthlyExpenses {
+                year,
+                month: group[0].get_date().month(),
+                subtotal: total_in(rates, group.iter().map(|expense| (expense.get_price(), expense.get_date())), currency),
+                expenses: group
+            }
+        }).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::QueryError;
+    use crate::tests::test_pool;
+
+    //Row is built from literals, so only the connection is needed and no table is touched
+    async fn decode(columns: &str) -> Result<Expense, QueryError> {
+        sqlx::query_as::<_, Expense>(&format!("SELECT {columns}"))
+            .fetch_one(&test_pool().await)
+            .await
+            .map_err(QueryError::match_sqlx_error)
+    }
+
+    const ROW: &str = "7 AS expenses_id, 2 AS expenses_creator, 'Аренда' AS expenses_name, \
+        CAST(-99.99 AS DECIMAL(15, 2)) AS price, 'RUB' AS currency, 5 AS expenses_category, \
+        CAST(NULL AS SIGNED) AS expenses_account, 6 AS expenses_job, 8 AS expenses_template, \
+        CAST(NULL AS SIGNED) AS expenses_asset, DATE '2023-12-31' AS expenses_date";
+
+    #[tokio::test]
+    #[ignore = "needs a MySQL test database in DATABASE_URL"]
+    async fn decodes_well_formed_row() {
+        let expense = decode(ROW).await.unwrap();
+
+        assert_eq!(expense.get_id(), 7);
+        assert_eq!(expense.get_name(), "Аренда");
+        assert_eq!(expense.get_price(), Money::from_minor(-9999, Currency::RUB));
+        assert_eq!(expense.get_category(), 5);
+        assert_eq!(expense.get_account(), None);
+        assert_eq!(expense.get_job(), Some(6));
+        assert_eq!(expense.get_template(), Some(8));
+        assert_eq!(expense.get_asset(), None);
+        assert_eq!(expense.get_date(), Date::from_calendar_date(2023, Month::December, 31).unwrap());
+    }
+
+    #[tokio::test]
+    #[ignore = "needs a MySQL test database in DATABASE_URL"]
+    async fn missing_column_is_decoding_problem() {
+        let without_asset = ROW.replace("CAST(NULL AS SIGNED) AS expenses_asset, ", "");
+
+        assert_eq!(decode(&without_asset).await.err(), Some(QueryError::DecodingProblem));
     }
-}
\ No newline at end of file
+
+    #[tokio::test]
+    #[ignore = "needs a MySQL test database in DATABASE_URL"]
+    async fn mistyped_column_is_decoding_problem() {
+        let text_date = ROW.replace("DATE '2023-12-31' AS expenses_date", "'вчера' AS expenses_date");
+        let text_price = ROW.replace("CAST(-99.99 AS DECIMAL(15, 2)) AS price", "'много' AS price");
+
+        assert_eq!(decode(&text_date).await.err(), Some(QueryError::DecodingProblem));
+        assert_eq!(decode(&text_price).await.err(), Some(QueryError::DecodingProblem));
+    }
+}
@@ -0,0 +1,301 @@
+use rust_decimal::{Decimal, RoundingStrategy};
+use time::{Date, Month};
+use crate::account::Account;
+use crate::allocation::period_bounds;
+use crate::budget::shift_month;
+use crate::category::CategoryKind;
+use crate::exchange::{ConversionError, ExchangeRate, total_in};
+use crate::expense::Expense;
+use crate::income::Income;
+use crate::money::{Currency, Money};
+use crate::recurring::Template;
+
+//Full months before the current one which one-off incomes and expenses are averaged over
+pub const HISTORY_MONTHS: u32 = 3;
+
+//Everything the forecast is calculated from
+pub struct ForecastInputs<'a> {
+    pub accounts: &'a [Account],
+    pub incomes: &'a [Income],
+    pub expenses: &'a [Expense],
+    pub templates: &'a [Template],
+    pub rates: &'a [ExchangeRate]
+}
+
+pub struct ForecastMonth {
+    pub year: i32,
+    pub month: Month,
+    //occurrences of recurring templates within the month
+    pub recurring_incomes: Money,
+    pub recurring_expenses: Money,
+    //average of one-off notes of the past months, the current month doesn't get it
+    pub expected_incomes: Money,
+    pub expected_expenses: Money,
+    //at the end of the month
+    pub balance: Money
+}
+
+pub struct Forecast {
+    //balance at the end of today
+    pub opening: Money,
+    //the current month first, then the months of the horizon
+    pub months: Vec<ForecastMonth>
+}
+
+impl Forecast {
+    //First month which ends with a negative balance
+    pub fn first_negative(&self) -> Option<&ForecastMonth> {
+        self.months.iter().find(|month| month.balance.is_negative())
+    }
+}
+
+//Balance of today is the opening balances of wallets plus incomes minus expenses dated up to today.
+//Depreciation doesn't take money from wallets, so it's left out, transfers between wallets don't change the total.
+//The rest of the current month gets only recurring occurrences after today, each of the next `horizon` months
+//gets its recurring occurrences plus the monthly average of one-off notes. Future amounts are converted with the latest known rates.
+pub fn forecast(inputs: &ForecastInputs<'_>, today: Date, horizon: u32, currency: Currency) -> Result<Forecast, ConversionError> {
+    let ForecastInputs {accounts, incomes, expenses, templates, rates} = *inputs;
+    let opening = balance_on(accounts, incomes, expenses, rates, today, currency)?;
+
+    let current = (today.year(), today.month());
+    let (expected_incomes, expected_expenses) = averages(inputs, current, currency)?;
+
+    let recurring = |kind: CategoryKind, from: Date, to: Date| total_in(
+        rates,
+        templates
+            .iter()
+            .filter(|template| template.get_kind() == kind)
+            .flat_map(|template| template.occurrences_between(from, to).into_iter().map(move |date| (template.get_price(), date))),
+        currency
+    );
+
+    let mut balance = opening;
+    let mut months = Vec::with_capacity(horizon as usize + 1);
+    let mut month = current;
+
+    for index in 0..=horizon {
+        let (first_day, last_day) = period_bounds(month);
+        let (from, expected) = match index {
+            0 => (today.next_day().unwrap_or(last_day), None),
+            _ => (first_day, Some((expected_incomes, expected_expenses)))
+        };
+        let (expected_incomes, expected_expenses) = expected.unwrap_or((Money::zero(currency), Money::zero(currency)));
+
+        let (recurring_incomes, recurring_expenses) = match from > today {
+            true => (recurring(CategoryKind::Income, from, last_day)?, recurring(CategoryKind::Expense, from, last_day)?),
+            false => (Money::zero(currency), Money::zero(currency))
+        };
+
+        balance = balance
+            .checked_add(recurring_incomes)?
+            .checked_sub(recurring_expenses)?
+            .checked_add(expected_incomes)?
+            .checked_sub(expected_expenses)?;
+
+        months.push(ForecastMonth {
+            year: month.0,
+            month: month.1,
+            recurring_incomes,
+            recurring_expenses,
+            expected_incomes,
+            expected_expenses,
+            balance
+        });
+
+        month = shift_month(month, true);
+    }
+
+    Ok(Forecast {opening, months})
+}
+
+//Opening balances of wallets plus incomes minus expenses dated up to the end of the date, depreciation left out
+pub fn balance_on(accounts: &[Account], incomes: &[Income], expenses: &[Expense], rates: &[ExchangeRate], date: Date, currency: Currency) -> Result<Money, ConversionError> {
+    let cash_expenses = expenses.iter().filter(|expense| expense.get_asset().is_none());
+
+    Ok(total_in(rates, accounts.iter().map(|account| (account.get_opening(), date)), currency)?
+        .checked_add(total_in(rates, incomes.iter().filter(|income| income.get_date() <= date).map(|income| (income.get_price(), income.get_date())), currency)?)?
+        .checked_sub(total_in(rates, cash_expenses.filter(|expense| expense.get_date() <= date).map(|expense| (expense.get_price(), expense.get_date())), currency)?)?)
+}
+
+//Monthly averages of one-off incomes and expenses. Months before the first note don't count,
+//so a short history isn't diluted with empty months
+fn averages(inputs: &ForecastInputs<'_>, current: (i32, Month), currency: Currency) -> Result<(Money, Money), ConversionError> {
+    let one_off_incomes = || inputs.incomes.iter().filter(|income| income.get_template().is_none());
+    let one_off_expenses = || inputs.expenses.iter().filter(|expense| expense.get_template().is_none() && expense.get_asset().is_none());
+
+    let first_note = one_off_incomes()
+        .map(|income| income.get_date())
+        .chain(one_off_expenses().map(|expense| expense.get_date()))
+        .min();
+
+    let Some(first_note) = first_note else {
+        return Ok((Money::zero(currency), Money::zero(currency)))
+    };
+
+    let mut from = period_bounds(current).0;
+    let mut counted = 0;
+    let mut month = current;
+
+    for _ in 0..HISTORY_MONTHS {
+        month = shift_month(month, false);
+        let (first_day, last_day) = period_bounds(month);
+
+        if last_day < first_note {
+            break
+        }
+
+        from = first_day;
+        counted += 1;
+    }
+
+    if counted == 0 {
+        return Ok((Money::zero(currency), Money::zero(currency)))
+    }
+
+    let to = period_bounds(shift_month(current, false)).1;
+    let in_history = |date: Date| date >= from && date <= to;
+
+    let incomes = total_in(inputs.rates, one_off_incomes().filter(|income| in_history(income.get_date())).map(|income| (income.get_price(), income.get_date())), currency)?;
+    let expenses = total_in(inputs.rates, one_off_expenses().filter(|expense| in_history(expense.get_date())).map(|expense| (expense.get_price(), expense.get_date())), currency)?;
+
+    let average = |total: Money| Money::from_decimal(
+        (total.to_decimal() / Decimal::from(counted)).round_dp_with_strategy(2, RoundingStrategy::MidpointAwayFromZero),
+        currency
+    );
+
+    Ok((average(incomes)?, average(expenses)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pages::notes_page::NoteDraft;
+    use crate::recurring::{Period, TemplateDraft};
+
+    const RUB: Currency = Currency::RUB;
+
+    fn date(year: i32, month: Month, day: u8) -> Date {
+        Date::from_calendar_date(year, month, day).unwrap()
+    }
+
+    fn rub(minor: i64) -> Money {
+        Money::from_minor(minor, RUB)
+    }
+
+    fn draft(price: Money, date: Date) -> NoteDraft {
+        NoteDraft {name: String::new(), price, category: 1, account: None, job: None, date}
+    }
+
+    fn income(id: i32, price: Money, date: Date) -> Income {
+        Income::new(id, 1, draft(price, date))
+    }
+
+    fn expense(id: i32, price: Money, date: Date) -> Expense {
+        Expense::new(id, 1, draft(price, date))
+    }
+
+    fn monthly(id: i32, kind: CategoryKind, price: Money, start: Date) -> Template {
+        Template::new(id, TemplateDraft {kind, name: String::new(), price, category: 1, period: Period::Monthly, start, end: None})
+    }
+
+    fn run(accounts: &[Account], incomes: &[Income], expenses: &[Expense], templates: &[Template], horizon: u32) -> Forecast {
+        let inputs = ForecastInputs {accounts, incomes, expenses, templates, rates: &[]};
+        forecast(&inputs, date(2024, Month::May, 15), horizon, RUB).unwrap()
+    }
+
+    #[test]
+    fn opening_leaves_out_depreciation_and_future_notes() {
+        let accounts = [Account::new(1, String::new(), rub(100_000))];
+        let incomes = [
+            income(1, rub(50_000), date(2024, Month::May, 1)),
+            income(2, rub(70_000), date(2024, Month::May, 20))
+        ];
+        let expenses = [
+            expense(1, rub(20_000), date(2024, Month::May, 15)),
+            expense(2, rub(30_000), date(2024, Month::April, 30)).with_source(None, Some(1))
+        ];
+
+        assert_eq!(run(&accounts, &incomes, &expenses, &[], 0).opening, rub(130_000));
+    }
+
+    #[test]
+    fn current_month_gets_recurring_only_after_today() {
+        let templates = [
+            monthly(1, CategoryKind::Income, rub(1_000), date(2024, Month::January, 10)),
+            monthly(2, CategoryKind::Income, rub(2_000), date(2024, Month::January, 20)),
+            monthly(3, CategoryKind::Expense, rub(500), date(2024, Month::January, 15))
+        ];
+
+        let forecast = run(&[], &[], &[], &templates, 1);
+
+        assert_eq!(forecast.months[0].recurring_incomes, rub(2_000));
+        assert_eq!(forecast.months[0].recurring_expenses, rub(0));
+        assert_eq!(forecast.months[1].recurring_incomes, rub(3_000));
+        assert_eq!(forecast.months[1].recurring_expenses, rub(500));
+        assert_eq!(forecast.months[1].balance, rub(4_500));
+    }
+
+    #[test]
+    fn short_history_is_not_diluted() {
+        //The only full month of history is April, so its total is the average
+        let expenses = [
+            expense(1, rub(30_000), date(2024, Month::April, 3)),
+            expense(2, rub(99_999), date(2024, Month::April, 5)).with_source(Some(1), None),
+            expense(3, rub(40_000), date(2024, Month::May, 2))
+        ];
+
+        let forecast = run(&[], &[], &expenses, &[], 2);
+
+        assert_eq!(forecast.months[0].expected_expenses, rub(0));
+        assert_eq!(forecast.months[1].expected_expenses, rub(30_000));
+        assert_eq!(forecast.months[2].expected_expenses, rub(30_000));
+    }
+
+    #[test]
+    fn history_is_limited_to_last_months() {
+        let incomes = [
+            income(1, rub(90_000), date(2023, Month::December, 1)),
+            income(2, rub(3_000), date(2024, Month::February, 1)),
+            income(3, rub(6_000), date(2024, Month::April, 1))
+        ];
+
+        let forecast = run(&[], &incomes, &[], &[], 1);
+
+        assert_eq!(forecast.months[1].expected_incomes, rub(3_000));
+    }
+
+    #[test]
+    fn averages_are_rounded_half_away_from_zero() {
+        let incomes = [
+            income(1, rub(2), date(2024, Month::March, 10)),
+            income(2, rub(3), date(2024, Month::April, 10))
+        ];
+        let expenses = [
+            expense(1, rub(1_000), date(2024, Month::February, 1)),
+            expense(2, rub(1), date(2024, Month::April, 1))
+        ];
+
+        //Both kinds are averaged over the same months, from the first note of either
+        let incomes_only = run(&[], &incomes, &[], &[], 1);
+        let expenses_only = run(&[], &[], &expenses, &[], 1);
+
+        //0.05 / 2 = 0.025
+        assert_eq!(incomes_only.months[1].expected_incomes, rub(3));
+        //10.01 / 3 = 3.3366...
+        assert_eq!(expenses_only.months[1].expected_expenses, rub(334));
+    }
+
+    #[test]
+    fn first_negative_month_is_found() {
+        let accounts = [Account::new(1, String::new(), rub(10_000))];
+        let templates = [monthly(1, CategoryKind::Expense, rub(6_000), date(2024, Month::June, 1))];
+
+        let forecast = run(&accounts, &[], &[], &templates, 3);
+        let negative = forecast.first_negative().unwrap();
+
+        assert_eq!((negative.year, negative.month), (2024, Month::July));
+        assert_eq!(negative.balance, rub(-2_000));
+
+        assert!(run(&accounts, &[], &[], &templates, 1).first_negative().is_none());
+    }
+}
@@ -1,49 +1,124 @@
-use sqlx::mysql::MySqlRow;
-use sqlx::Row;
+use sqlx::FromRow;
+use crate::pages::notes_page::NoteDraft;
+use crate::money::Money;
 use sqlx::types::time::Date;
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, FromRow)]
 pub struct Income {
     incomes_id: i32,
     incomes_creator: i32,
     incomes_name: String,
-    incomes_price: u32,
+    //read from `price` and `currency` columns
+    #[sqlx(flatten)]
+    incomes_price: Money,
     incomes_category: i32,
+    //None for notes entered before accounts were added
+    incomes_account: Option<i32>,
+    //order or project the note belongs to
+    incomes_job: Option<i32>,
+    //recurring template the note was created from
+    incomes_template: Option<i32>,
     incomes_date: Date
 }
 
 impl Income {
-    pub fn new(
-        incomes_id: i32,
-        incomes_creator: i32,
-        incomes_name: String,
-        incomes_price: u32,
-        incomes_category: i32,
-        incomes_date: Date
-    ) -> Self {
+    pub fn new(incomes_id: i32, incomes_creator: i32, draft: NoteDraft) -> Self {
         Self {
             incomes_id,
             incomes_creator,
-            incomes_name,
-            incomes_price,
-            incomes_category,
-            incomes_date,
+            incomes_name: draft.name,
+            incomes_price: draft.price,
+            incomes_category: draft.category,
+            incomes_account: draft.account,
+            incomes_job: draft.job,
+            incomes_template: None,
+            incomes_date: draft.date
         }
     }
 
-    pub fn collect_from_query_vec(incomes_rows: Vec<MySqlRow>) -> Vec<Self> {
-        incomes_rows
-            .into_iter()
-            .map(|income| {
-                Self::new(
-                    income.get("incomes_id"),
-                    income.get("incomes_creator"),
-                    income.get("incomes_name"),
-                    income.get("incomes_price"),
-                    income.get("incomes_category"),
-                    income.get("incomes_date")
-                )
-            }).collect()
+    pub fn get_id(&self) -> i32 {
+        self.incomes_id
+    }
+
+    pub fn get_name(&self) -> &str {
+        &self.incomes_name
+    }
+
+    pub fn get_price(&self) -> Money {
+        self.incomes_price
+    }
+
+    pub fn get_category(&self) -> i32 {
+        self.incomes_category
+    }
 
+    pub fn get_account(&self) -> Option<i32> {
+        self.incomes_account
     }
-}
\ No newline at end of file
+
+    pub fn get_job(&self) -> Option<i32> {
+        self.incomes_job
+    }
+
+    pub fn get_template(&self) -> Option<i32> {
+        self.incomes_template
+    }
+
+    pub fn get_date(&self) -> Date {
+        self.incomes_date
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use time::Month;
+    use crate::error::QueryError;
+    use crate::money::Currency;
+    use crate::tests::test_pool;
+
+    //Row is built from literals, so only the connection is needed and no table is touched
+    async fn decode(columns: &str) -> Result<Income, QueryError> {
+        sqlx::query_as::<_, Income>(&format!("SELECT {columns}"))
+            .fetch_one(&test_pool().await)
+            .await
+            .map_err(QueryError::match_sqlx_error)
+    }
+
+    const ROW: &str = "1 AS incomes_id, 2 AS incomes_creator, 'Зарплата' AS incomes_name, \
+        CAST(1234.50 AS DECIMAL(15, 2)) AS price, 'USD' AS currency, 3 AS incomes_category, \
+        4 AS incomes_account, CAST(NULL AS SIGNED) AS incomes_job, CAST(NULL AS SIGNED) AS incomes_template, \
+        DATE '2024-03-15' AS incomes_date";
+
+    #[tokio::test]
+    #[ignore = "needs a MySQL test database in DATABASE_URL"]
+    async fn decodes_well_formed_row() {
+        let income = decode(ROW).await.unwrap();
+
+        assert_eq!(income.get_id(), 1);
+        assert_eq!(income.get_name(), "Зарплата");
+        assert_eq!(income.get_price(), Money::from_minor(123450, Currency::USD));
+        assert_eq!(income.get_category(), 3);
+        assert_eq!(income.get_account(), Some(4));
+        assert_eq!(income.get_job(), None);
+        assert_eq!(income.get_template(), None);
+        assert_eq!(income.get_date(), Date::from_calendar_date(2024, Month::March, 15).unwrap());
+    }
+
+    #[tokio::test]
+    #[ignore = "needs a MySQL test database in DATABASE_URL"]
+    async fn missing_column_is_decoding_problem() {
+        let without_date = ROW.replace(", DATE '2024-03-15' AS incomes_date", "");
+
+        assert_eq!(decode(&without_date).await.err(), Some(QueryError::DecodingProblem));
+    }
+
+    #[tokio::test]
+    #[ignore = "needs a MySQL test database in DATABASE_URL"]
+    async fn mistyped_column_is_decoding_problem() {
+        let text_id = ROW.replace("1 AS incomes_id", "'one' AS incomes_id");
+        let broken_currency = ROW.replace("'USD' AS currency", "'1$' AS currency");
+
+        assert_eq!(decode(&text_id).await.err(), Some(QueryError::DecodingProblem));
+        assert_eq!(decode(&broken_currency).await.err(), Some(QueryError::DecodingProblem));
+    }
+}
@@ -0,0 +1,544 @@
+use std::collections::VecDeque;
+use std::fmt::{Display, Formatter};
+use rust_decimal::{Decimal, RoundingStrategy};
+use sqlx::FromRow;
+use time::Date;
+use crate::money::{Currency, Money, MoneyError};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CostMethod {
+    //the oldest lots are issued first
+    Fifo,
+    //the newest lots are issued first
+    Lifo,
+    //every issue costs the average unit cost of the stock at its date
+    WeightedAverage
+}
+
+impl CostMethod {
+    pub const ALL: [CostMethod; 3] = [CostMethod::Fifo, CostMethod::Lifo, CostMethod::WeightedAverage];
+
+    //Value stored in ITEM_METHOD column
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CostMethod::Fifo => "FIFO",
+            CostMethod::Lifo => "LIFO",
+            CostMethod::WeightedAverage => "AVERAGE"
+        }
+    }
+}
+
+impl TryFrom<String> for CostMethod {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        match value.as_str() {
+            "FIFO" => Ok(CostMethod::Fifo),
+            "LIFO" => Ok(CostMethod::Lifo),
+            "AVERAGE" => Ok(CostMethod::WeightedAverage),
+            other => Err(format!("unknown cost method: {other}"))
+        }
+    }
+}
+
+impl Display for CostMethod {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CostMethod::Fifo => write!(f, "ФИФО"),
+            CostMethod::Lifo => write!(f, "ЛИФО"),
+            CostMethod::WeightedAverage => write!(f, "Средневзвешенная")
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, FromRow)]
+pub struct Item {
+    item_id: i32,
+    item_name: String,
+    item_unit: String,
+    #[sqlx(try_from = "String")]
+    item_method: CostMethod,
+    //all lots of the item are in this currency
+    item_currency: Currency
+}
+
+impl Item {
+    pub fn new(item_id: i32, draft: ItemDraft) -> Self {
+        Self {
+            item_id,
+            item_name: draft.name,
+            item_unit: draft.unit,
+            item_method: draft.method,
+            item_currency: draft.currency
+        }
+    }
+
+    pub fn get_id(&self) -> i32 {
+        self.item_id
+    }
+
+    pub fn get_name(&self) -> &str {
+        &self.item_name
+    }
+
+    pub fn get_unit(&self) -> &str {
+        &self.item_unit
+    }
+
+    pub fn get_method(&self) -> CostMethod {
+        self.item_method
+    }
+
+    pub fn set_method(&mut self, method: CostMethod) {
+        self.item_method = method
+    }
+
+    pub fn get_currency(&self) -> Currency {
+        self.item_currency
+    }
+}
+
+//Shown in pick lists
+impl Display for Item {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.item_name)
+    }
+}
+
+pub struct ItemDraft {
+    pub name: String,
+    pub unit: String,
+    pub method: CostMethod,
+    pub currency: Currency
+}
+
+#[derive(Clone, Debug, FromRow)]
+pub struct Lot {
+    lot_id: i32,
+    lot_item: i32,
+    lot_date: Date,
+    lot_quantity: Decimal,
+    //read from `price` and `currency` columns, currency is the one of the item
+    #[sqlx(flatten)]
+    lot_unit_cost: Money
+}
+
+impl Lot {
+    pub fn new(lot_id: i32, draft: LotDraft) -> Self {
+        Self {
+            lot_id,
+            lot_item: draft.item,
+            lot_date: draft.date,
+            lot_quantity: draft.quantity,
+            lot_unit_cost: draft.unit_cost
+        }
+    }
+
+    pub fn get_id(&self) -> i32 {
+        self.lot_id
+    }
+
+    pub fn get_item(&self) -> i32 {
+        self.lot_item
+    }
+
+    pub fn get_date(&self) -> Date {
+        self.lot_date
+    }
+
+    pub fn get_quantity(&self) -> Decimal {
+        self.lot_quantity
+    }
+
+    pub fn get_unit_cost(&self) -> Money {
+        self.lot_unit_cost
+    }
+}
+
+pub struct LotDraft {
+    pub item: i32,
+    pub date: Date,
+    pub quantity: Decimal,
+    pub unit_cost: Money
+}
+
+#[derive(Clone, Debug, FromRow)]
+pub struct Issue {
+    issue_id: i32,
+    issue_item: i32,
+    issue_date: Date,
+    issue_quantity: Decimal
+}
+
+impl Issue {
+    pub fn new(issue_id: i32, draft: IssueDraft) -> Self {
+        Self {
+            issue_id,
+            issue_item: draft.item,
+            issue_date: draft.date,
+            issue_quantity: draft.quantity
+        }
+    }
+
+    pub fn get_id(&self) -> i32 {
+        self.issue_id
+    }
+
+    pub fn get_item(&self) -> i32 {
+        self.issue_item
+    }
+
+    pub fn get_date(&self) -> Date {
+        self.issue_date
+    }
+
+    pub fn get_quantity(&self) -> Decimal {
+        self.issue_quantity
+    }
+}
+
+pub struct IssueDraft {
+    pub item: i32,
+    pub date: Date,
+    pub quantity: Decimal
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum InventoryError {
+    //more was issued on the date than there was in stock, by the given quantity
+    Shortage(Date, Decimal),
+    Money(MoneyError)
+}
+
+impl Display for InventoryError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            InventoryError::Shortage(date, missing) => write!(
+                f,
+                "{:02}.{:02}.{} списано на {} больше, чем было на складе",
+                date.day(),
+                u8::from(date.month()),
+                date.year(),
+                missing.normalize()
+            ),
+            InventoryError::Money(err) => write!(f, "{err}")
+        }
+    }
+}
+
+impl From<MoneyError> for InventoryError {
+    fn from(value: MoneyError) -> Self {
+        InventoryError::Money(value)
+    }
+}
+
+pub struct Valuation {
+    //cost of the quantity issued within the period
+    pub cogs: Money,
+    pub issued: Decimal,
+    //stock left at the end of the period
+    pub ending_quantity: Decimal,
+    pub ending_value: Money
+}
+
+//Quantity and unit cost of a part of the stock
+struct Layer {
+    quantity: Decimal,
+    unit_cost: Decimal
+}
+
+//Replays purchases and issues of the item up to the end of the period (purchases of a day come before its issues).
+//Issues before the start of the period only change the stock, None bounds mean the period is unbounded.
+pub fn value(item: &Item, lots: &[Lot], issues: &[Issue], method: CostMethod, from: Option<Date>, to: Option<Date>) -> Result<Valuation, InventoryError> {
+    let before_end = |date: Date| !matches!(to, Some(to) if date > to);
+
+    let mut events = lots
+        .iter()
+        .filter(|lot| lot.lot_item == item.item_id && before_end(lot.lot_date))
+        .map(|lot| (lot.lot_date, Some(lot.lot_unit_cost.to_decimal()), lot.lot_quantity))
+        .chain(
+            issues
+                .iter()
+                .filter(|issue| issue.issue_item == item.item_id && before_end(issue.issue_date))
+                .map(|issue| (issue.issue_date, None, issue.issue_quantity))
+        )
+        .collect::<Vec<(Date, Option<Decimal>, Decimal)>>();
+
+    events.sort_by(|a, b| a.0.cmp(&b.0).then(b.1.is_some().cmp(&a.1.is_some())));
+
+    let mut stock = VecDeque::<Layer>::new();
+    let mut cogs = Decimal::ZERO;
+    let mut issued = Decimal::ZERO;
+
+    for (date, unit_cost, quantity) in events {
+        match unit_cost {
+            Some(unit_cost) => receive(&mut stock, method, quantity, unit_cost)?,
+            None => {
+                let cost = issue(&mut stock, method, quantity).map_err(|missing| InventoryError::Shortage(date, missing))?;
+
+                if !matches!(from, Some(from) if date < from) {
+                    cogs = cogs.checked_add(cost).ok_or(MoneyError::Overflow)?;
+                    issued += quantity;
+                }
+            }
+        }
+    }
+
+    let ending_value = stock
+        .iter()
+        .try_fold(Decimal::ZERO, |total, layer| layer.quantity.checked_mul(layer.unit_cost).and_then(|cost| total.checked_add(cost)))
+        .ok_or(MoneyError::Overflow)?;
+
+    Ok(Valuation {
+        cogs: to_money(cogs, item.item_currency)?,
+        issued,
+        ending_quantity: stock.iter().map(|layer| layer.quantity).sum(),
+        ending_value: to_money(ending_value, item.item_currency)?
+    })
+}
+
+//Average cost keeps the whole stock in one layer, so every purchase changes the unit cost of it
+fn receive(stock: &mut VecDeque<Layer>, method: CostMethod, quantity: Decimal, unit_cost: Decimal) -> Result<(), MoneyError> {
+    match (method, stock.front_mut()) {
+        (CostMethod::WeightedAverage, Some(layer)) => {
+            let total_quantity = layer.quantity + quantity;
+            let total_cost = layer.quantity
+                .checked_mul(layer.unit_cost)
+                .zip(quantity.checked_mul(unit_cost))
+                .and_then(|(old, new)| old.checked_add(new))
+                .ok_or(MoneyError::Overflow)?;
+
+            layer.unit_cost = if total_quantity.is_zero() {unit_cost} else {total_cost / total_quantity};
+            layer.quantity = total_quantity;
+        }
+        _ => stock.push_back(Layer {quantity, unit_cost})
+    }
+
+    Ok(())
+}
+
+//Cost of the issued quantity, Err with the missing quantity when there isn't enough in stock
+fn issue(stock: &mut VecDeque<Layer>, method: CostMethod, quantity: Decimal) -> Result<Decimal, Decimal> {
+    let mut left = quantity;
+    let mut cost = Decimal::ZERO;
+
+    while left > Decimal::ZERO {
+        let layer = match method {
+            CostMethod::Lifo => stock.back_mut(),
+            CostMethod::Fifo | CostMethod::WeightedAverage => stock.front_mut()
+        };
+
+        let Some(layer) = layer else {
+            return Err(left)
+        };
+
+        let taken = left.min(layer.quantity);
+        cost += taken * layer.unit_cost;
+        layer.quantity -= taken;
+        left -= taken;
+
+        if layer.quantity.is_zero() {
+            match method {
+                CostMethod::Lifo => stock.pop_back(),
+                CostMethod::Fifo | CostMethod::WeightedAverage => stock.pop_front()
+            };
+        }
+    }
+
+    Ok(cost)
+}
+
+fn to_money(amount: Decimal, currency: Currency) -> Result<Money, MoneyError> {
+    Money::from_decimal(amount.round_dp_with_strategy(2, RoundingStrategy::MidpointAwayFromZero), currency)
+}
+
+//Valuation of the item in the period by every method, to see how much the choice of the method changes
+pub fn compare(item: &Item, lots: &[Lot], issues: &[Issue], from: Option<Date>, to: Option<Date>) -> Vec<(CostMethod, Result<Valuation, InventoryError>)> {
+    CostMethod::ALL
+        .into_iter()
+        .map(|method| (method, value(item, lots, issues, method, from, to)))
+        .collect()
+}
+
+//Quantity in stock of the item at the end of the date
+pub fn on_hand(item_id: i32, lots: &[Lot], issues: &[Issue], date: Date) -> Decimal {
+    let bought = lots
+        .iter()
+        .filter(|lot| lot.lot_item == item_id && lot.lot_date <= date)
+        .map(|lot| lot.lot_quantity)
+        .sum::<Decimal>();
+    let issued = issues
+        .iter()
+        .filter(|issue| issue.issue_item == item_id && issue.issue_date <= date)
+        .map(|issue| issue.issue_quantity)
+        .sum::<Decimal>();
+
+    bought - issued
+}
+
+//New issue can't leave the stock negative on its date or on any later one
+pub fn issue_fits(item_id: i32, lots: &[Lot], issues: &[Issue], date: Date, quantity: Decimal) -> Result<(), InventoryError> {
+    let later_dates = lots
+        .iter()
+        .filter(|lot| lot.lot_item == item_id)
+        .map(|lot| lot.lot_date)
+        .chain(issues.iter().filter(|issue| issue.issue_item == item_id).map(|issue| issue.issue_date))
+        .filter(|other| *other > date);
+
+    for date in std::iter::once(date).chain(later_dates) {
+        let left = on_hand(item_id, lots, issues, date) - quantity;
+
+        if left.is_sign_negative() && !left.is_zero() {
+            return Err(InventoryError::Shortage(date, -left))
+        }
+    }
+
+    Ok(())
+}
+
+//Lot can't be deleted while the issues of its item need it, the stock would go negative on the returned date
+pub fn lot_removable(lot: &Lot, lots: &[Lot], issues: &[Issue]) -> Result<(), InventoryError> {
+    let remaining = lots
+        .iter()
+        .filter(|other| other.lot_id != lot.lot_id)
+        .cloned()
+        .collect::<Vec<Lot>>();
+
+    let issue_dates = issues
+        .iter()
+        .filter(|issue| issue.issue_item == lot.lot_item && issue.issue_date >= lot.lot_date)
+        .map(|issue| issue.issue_date);
+
+    for date in issue_dates {
+        let left = on_hand(lot.lot_item, &remaining, issues, date);
+
+        if left.is_sign_negative() && !left.is_zero() {
+            return Err(InventoryError::Shortage(date, -left))
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use time::Month;
+
+    fn date(day: u8) -> Date {
+        Date::from_calendar_date(2024, Month::January, day).unwrap()
+    }
+
+    fn rub(minor: i64) -> Money {
+        Money::from_minor(minor, Currency::RUB)
+    }
+
+    fn item() -> Item {
+        Item::new(1, ItemDraft {name: String::new(), unit: "шт".to_string(), method: CostMethod::Fifo, currency: Currency::RUB})
+    }
+
+    fn lot(id: i32, day: u8, quantity: i64, unit_cost: i64) -> Lot {
+        Lot::new(id, LotDraft {item: 1, date: date(day), quantity: Decimal::from(quantity), unit_cost: rub(unit_cost)})
+    }
+
+    fn issue(id: i32, day: u8, quantity: i64) -> Issue {
+        Issue::new(id, IssueDraft {item: 1, date: date(day), quantity: Decimal::from(quantity)})
+    }
+
+    //10 units at 1.00, then 10 units at 2.00, 15 of them are issued
+    fn stock() -> (Vec<Lot>, Vec<Issue>) {
+        (vec![lot(1, 1, 10, 100), lot(2, 10, 10, 200)], vec![issue(1, 15, 15)])
+    }
+
+    fn valuation(method: CostMethod) -> Valuation {
+        let (lots, issues) = stock();
+        value(&item(), &lots, &issues, method, None, None).unwrap()
+    }
+
+    #[test]
+    fn fifo_issues_oldest_lots_first() {
+        let valuation = valuation(CostMethod::Fifo);
+
+        assert_eq!(valuation.cogs, rub(2_000));
+        assert_eq!(valuation.ending_quantity, Decimal::from(5));
+        assert_eq!(valuation.ending_value, rub(1_000));
+    }
+
+    #[test]
+    fn lifo_issues_newest_lots_first() {
+        let valuation = valuation(CostMethod::Lifo);
+
+        assert_eq!(valuation.cogs, rub(2_500));
+        assert_eq!(valuation.ending_value, rub(500));
+    }
+
+    #[test]
+    fn weighted_average_uses_cost_of_the_whole_stock() {
+        let valuation = valuation(CostMethod::WeightedAverage);
+
+        assert_eq!(valuation.cogs, rub(2_250));
+        assert_eq!(valuation.ending_value, rub(750));
+    }
+
+    #[test]
+    fn cost_of_goods_plus_ending_value_is_the_same_for_every_method() {
+        let (lots, issues) = stock();
+
+        for (method, valuation) in compare(&item(), &lots, &issues, None, None) {
+            let valuation = valuation.unwrap();
+            assert_eq!(valuation.cogs.checked_add(valuation.ending_value), Ok(rub(3_000)), "{method}");
+            assert_eq!(valuation.issued, Decimal::from(15));
+        }
+    }
+
+    #[test]
+    fn issues_before_the_period_change_only_the_stock() {
+        let (lots, issues) = stock();
+        let valuation = value(&item(), &lots, &issues, CostMethod::Fifo, Some(date(16)), None).unwrap();
+
+        assert_eq!(valuation.cogs, rub(0));
+        assert_eq!(valuation.ending_value, rub(1_000));
+    }
+
+    #[test]
+    fn lot_of_the_day_is_received_before_its_issues() {
+        let lots = [lot(1, 5, 3, 100)];
+        let issues = [issue(1, 5, 3)];
+
+        assert_eq!(value(&item(), &lots, &issues, CostMethod::Lifo, None, None).unwrap().cogs, rub(300));
+    }
+
+    #[test]
+    fn issuing_more_than_in_stock_is_a_shortage() {
+        let (lots, _) = stock();
+        let issues = [issue(1, 15, 25)];
+
+        for method in CostMethod::ALL {
+            assert_eq!(
+                value(&item(), &lots, &issues, method, None, None).err(),
+                Some(InventoryError::Shortage(date(15), Decimal::from(5)))
+            );
+        }
+        assert_eq!(issue_fits(1, &lots, &[], date(15), Decimal::from(25)), Err(InventoryError::Shortage(date(15), Decimal::from(5))));
+        assert_eq!(issue_fits(1, &lots, &[], date(15), Decimal::from(20)), Ok(()));
+    }
+
+    #[test]
+    fn issue_may_not_take_stock_later_issues_need() {
+        let (lots, issues) = stock();
+
+        //Only 5 units are left after the issue of the 15th
+        assert!(issue_fits(1, &lots, &issues, date(12), Decimal::from(6)).is_err());
+        assert!(issue_fits(1, &lots, &issues, date(12), Decimal::from(5)).is_ok());
+    }
+
+    #[test]
+    fn lot_which_issues_need_is_not_removable() {
+        let (lots, issues) = stock();
+
+        assert_eq!(lot_removable(&lots[1], &lots, &issues), Err(InventoryError::Shortage(date(15), Decimal::from(5))));
+        assert_eq!(lot_removable(&lots[1], &lots, &[issue(1, 15, 10)]), Ok(()));
+        //Issues before the lot never took from it
+        assert_eq!(lot_removable(&lots[1], &lots, &[issue(1, 5, 10)]), Ok(()));
+    }
+}
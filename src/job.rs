@@ -0,0 +1,315 @@
+use std::fmt::{Display, Formatter};
+use rust_decimal::Decimal;
+use sqlx::FromRow;
+use time::Date;
+use crate::allocation::{Allocation, largest_remainder, period_bounds};
+use crate::exchange::{ConversionError, ExchangeRate, convert, total_in};
+use crate::expense::Expense;
+use crate::income::Income;
+use crate::money::{Currency, Money};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum JobStatus {
+    Planned,
+    Active,
+    Completed,
+    Cancelled
+}
+
+impl JobStatus {
+    pub const ALL: [JobStatus; 4] = [JobStatus::Planned, JobStatus::Active, JobStatus::Completed, JobStatus::Cancelled];
+
+    //Value stored in JOB_STATUS column
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            JobStatus::Planned => "PLANNED",
+            JobStatus::Active => "ACTIVE",
+            JobStatus::Completed => "COMPLETED",
+            JobStatus::Cancelled => "CANCELLED"
+        }
+    }
+}
+
+impl TryFrom<String> for JobStatus {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        match value.as_str() {
+            "PLANNED" => Ok(JobStatus::Planned),
+            "ACTIVE" => Ok(JobStatus::Active),
+            "COMPLETED" => Ok(JobStatus::Completed),
+            "CANCELLED" => Ok(JobStatus::Cancelled),
+            other => Err(format!("unknown job status: {other}"))
+        }
+    }
+}
+
+impl Display for JobStatus {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            JobStatus::Planned => write!(f, "Планируется"),
+            JobStatus::Active => write!(f, "В работе"),
+            JobStatus::Completed => write!(f, "Завершён"),
+            JobStatus::Cancelled => write!(f, "Отменён")
+        }
+    }
+}
+
+//Order or project of a client
+#[derive(Clone, Debug, PartialEq, FromRow)]
+pub struct Job {
+    job_id: i32,
+    job_name: String,
+    job_client: String,
+    job_start: Date,
+    //None while the end isn't known
+    job_end: Option<Date>,
+    #[sqlx(try_from = "String")]
+    job_status: JobStatus,
+    //overhead allocated to the center is shared between its jobs
+    job_center: Option<i32>
+}
+
+impl Job {
+    pub fn new(job_id: i32, draft: JobDraft) -> Self {
+        Self {
+            job_id,
+            job_name: draft.name,
+            job_client: draft.client,
+            job_start: draft.start,
+            job_end: draft.end,
+            job_status: draft.status,
+            job_center: draft.center
+        }
+    }
+
+    pub fn get_id(&self) -> i32 {
+        self.job_id
+    }
+
+    pub fn get_name(&self) -> &str {
+        &self.job_name
+    }
+
+    pub fn get_client(&self) -> &str {
+        &self.job_client
+    }
+
+    pub fn get_start(&self) -> Date {
+        self.job_start
+    }
+
+    pub fn get_end(&self) -> Option<Date> {
+        self.job_end
+    }
+
+    pub fn get_status(&self) -> JobStatus {
+        self.job_status
+    }
+
+    pub fn set_status(&mut self, status: JobStatus) {
+        self.job_status = status
+    }
+
+    pub fn get_center(&self) -> Option<i32> {
+        self.job_center
+    }
+}
+
+//Shown in pick lists
+impl Display for Job {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self.job_client.is_empty() {
+            true => write!(f, "{}", self.job_name),
+            false => write!(f, "{} ({})", self.job_name, self.job_client)
+        }
+    }
+}
+
+pub struct JobDraft {
+    pub name: String,
+    pub client: String,
+    pub start: Date,
+    pub end: Option<Date>,
+    pub status: JobStatus,
+    pub center: Option<i32>
+}
+
+pub struct JobProfit {
+    pub revenue: Money,
+    pub direct_costs: Money,
+    pub overhead: Money,
+    pub margin: Money
+}
+
+impl JobProfit {
+    //Margin as a share of revenue, None when there is no revenue yet
+    pub fn margin_percent(&self) -> Option<f64> {
+        match self.revenue.minor() {
+            0 => None,
+            revenue => Some(self.margin.minor() as f64 * 100.0 / revenue as f64)
+        }
+    }
+}
+
+//Overhead allocated to the center of the job in a month is shared between the jobs of that center
+//in proportion to their direct costs in the same month by the largest remainder, so the shares of all jobs
+//add up to the allocated amount. Months without direct costs give nothing, neither do refunds exceeding the costs.
+pub fn job_overhead(job: &Job, jobs: &[Job], expenses: &[Expense], allocations: &[Allocation], rates: &[ExchangeRate], currency: Currency) -> Result<Money, ConversionError> {
+    let Some(center) = job.job_center else {
+        return Ok(Money::zero(currency))
+    };
+
+    let direct_in = |job_id: i32, from: Date, to: Date| total_in(
+        rates,
+        expenses
+            .iter()
+            .filter(|expense| expense.get_job() == Some(job_id) && expense.get_date() >= from && expense.get_date() <= to)
+            .map(|expense| (expense.get_price(), expense.get_date())),
+        currency
+    );
+
+    let mut overhead = Money::zero(currency);
+
+    for allocation in allocations.iter().filter(|allocation| allocation.get_center() == center) {
+        let period = allocation.get_period();
+        let (from, to) = period_bounds((period.year(), period.month()));
+
+        let weights = jobs
+            .iter()
+            .filter(|other| other.job_center == Some(center))
+            .map(|other| Ok((other.job_id, Decimal::from(direct_in(other.job_id, from, to)?.minor().max(0)))))
+            .collect::<Result<Vec<(i32, Decimal)>, ConversionError>>()?;
+
+        if weights.iter().all(|(_, weight)| weight.is_zero()) {
+            continue
+        }
+
+        let allocated = convert(rates, allocation.get_amount(), currency, to)?;
+        let share = largest_remainder(allocated, &weights)?
+            .into_iter()
+            .find(|(job_id, _)| *job_id == job.job_id)
+            .map_or(Money::zero(currency), |(_, share)| share);
+
+        overhead = overhead.checked_add(share)?;
+    }
+
+    Ok(overhead)
+}
+
+//Revenue and direct costs are incomes and expenses linked to the job, converted with the rates of their dates
+pub fn profitability(job: &Job, jobs: &[Job], incomes: &[Income], expenses: &[Expense], allocations: &[Allocation], rates: &[ExchangeRate], currency: Currency) -> Result<JobProfit, ConversionError> {
+    let revenue = total_in(
+        rates,
+        incomes.iter().filter(|income| income.get_job() == Some(job.job_id)).map(|income| (income.get_price(), income.get_date())),
+        currency
+    )?;
+    let direct_costs = total_in(
+        rates,
+        expenses.iter().filter(|expense| expense.get_job() == Some(job.job_id)).map(|expense| (expense.get_price(), expense.get_date())),
+        currency
+    )?;
+    let overhead = job_overhead(job, jobs, expenses, allocations, rates, currency)?;
+
+    Ok(JobProfit {
+        revenue,
+        direct_costs,
+        overhead,
+        margin: revenue.checked_sub(direct_costs)?.checked_sub(overhead)?
+    })
+}
+
+//Used where the share is shown next to the amount
+pub fn percent_text(percent: Option<f64>) -> String {
+    match percent {
+        Some(percent) => format!("{:.1}%", percent),
+        None => String::from("—")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use time::Month;
+    use crate::allocation::AllocationDraft;
+    use crate::pages::notes_page::NoteDraft;
+
+    fn rub(minor: i64) -> Money {
+        Money::from_minor(minor, Currency::RUB)
+    }
+
+    fn date(month: Month, day: u8) -> Date {
+        Date::from_calendar_date(2024, month, day).unwrap()
+    }
+
+    fn job(job_id: i32, center: Option<i32>) -> Job {
+        Job::new(job_id, JobDraft {
+            name: format!("Заказ {job_id}"),
+            client: String::new(),
+            start: date(Month::January, 1),
+            end: None,
+            status: JobStatus::Active,
+            center
+        })
+    }
+
+    fn draft(job: i32, minor: i64, date: Date) -> NoteDraft {
+        NoteDraft {
+            name: String::new(),
+            price: rub(minor),
+            category: 1,
+            account: None,
+            job: Some(job),
+            date
+        }
+    }
+
+    fn allocation(month: Month, center: i32, minor: i64) -> Allocation {
+        Allocation::new(0, date(month, 1), AllocationDraft {category: 1, center, amount: rub(minor)})
+    }
+
+    //Three jobs of center 1 with equal direct costs in March, job 4 has no center
+    fn setup() -> (Vec<Job>, Vec<Expense>) {
+        let jobs = vec![job(1, Some(1)), job(2, Some(1)), job(3, Some(1)), job(4, None)];
+        let expenses = (1..=4)
+            .map(|job| Expense::new(job, 1, draft(job, 1_000, date(Month::March, 10))))
+            .collect();
+
+        (jobs, expenses)
+    }
+
+    #[test]
+    fn shares_of_jobs_add_up_to_the_allocation() {
+        let (jobs, expenses) = setup();
+        let allocations = [allocation(Month::March, 1, 100)];
+
+        let shares = jobs[..3]
+            .iter()
+            .map(|job| job_overhead(job, &jobs, &expenses, &allocations, &[], Currency::RUB).unwrap())
+            .collect::<Vec<Money>>();
+
+        assert_eq!(shares, [rub(34), rub(33), rub(33)]);
+    }
+
+    #[test]
+    fn no_overhead_without_center_or_direct_costs() {
+        let (jobs, expenses) = setup();
+        let allocations = [allocation(Month::February, 1, 100), allocation(Month::March, 2, 100)];
+
+        assert_eq!(job_overhead(&jobs[0], &jobs, &expenses, &allocations, &[], Currency::RUB), Ok(rub(0)));
+        assert_eq!(job_overhead(&jobs[3], &jobs, &expenses, &[allocation(Month::March, 1, 100)], &[], Currency::RUB), Ok(rub(0)));
+    }
+
+    #[test]
+    fn margin_after_overhead() {
+        let (jobs, expenses) = setup();
+        let incomes = [Income::new(1, 1, draft(1, 2_000, date(Month::March, 20))), Income::new(2, 1, draft(2, 500, date(Month::April, 1)))];
+        let allocations = [allocation(Month::March, 1, 300)];
+
+        let profit = profitability(&jobs[0], &jobs, &incomes, &expenses, &allocations, &[], Currency::RUB).unwrap();
+
+        assert_eq!((profit.revenue, profit.direct_costs, profit.overhead, profit.margin), (rub(2_000), rub(1_000), rub(100), rub(900)));
+        assert_eq!(profit.margin_percent(), Some(45.0));
+        assert_eq!(profitability(&jobs[3], &jobs, &incomes, &expenses, &allocations, &[], Currency::RUB).unwrap().margin_percent(), None);
+    }
+}
@@ -0,0 +1,475 @@
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+use sqlx::FromRow;
+use sqlx::mysql::MySqlRow;
+use time::Date;
+use crate::account::Account;
+use crate::category::Category;
+use crate::cost_center::CostCenter;
+use crate::error::QueryError;
+use crate::money::{Currency, Money, MoneyError};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LedgerKind {
+    Asset,
+    Liability,
+    Equity,
+    Revenue,
+    Expense
+}
+
+impl LedgerKind {
+    //Value stored in LEDGER_KIND column
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LedgerKind::Asset => "ASSET",
+            LedgerKind::Liability => "LIABILITY",
+            LedgerKind::Equity => "EQUITY",
+            LedgerKind::Revenue => "REVENUE",
+            LedgerKind::Expense => "EXPENSE"
+        }
+    }
+}
+
+impl TryFrom<String> for LedgerKind {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        match value.as_str() {
+            "ASSET" => Ok(LedgerKind::Asset),
+            "LIABILITY" => Ok(LedgerKind::Liability),
+            "EQUITY" => Ok(LedgerKind::Equity),
+            "REVENUE" => Ok(LedgerKind::Revenue),
+            "EXPENSE" => Ok(LedgerKind::Expense),
+            other => Err(format!("unknown ledger kind: {other}"))
+        }
+    }
+}
+
+impl Display for LedgerKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LedgerKind::Asset => write!(f, "Актив"),
+            LedgerKind::Liability => write!(f, "Обязательство"),
+            LedgerKind::Equity => write!(f, "Капитал"),
+            LedgerKind::Revenue => write!(f, "Доход"),
+            LedgerKind::Expense => write!(f, "Расход")
+        }
+    }
+}
+
+//Account of the chart of accounts, not to be confused with wallets (crate::account::Account)
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, FromRow)]
+pub struct LedgerAccount {
+    ledger_code: String,
+    ledger_name: String,
+    #[sqlx(try_from = "String")]
+    ledger_kind: LedgerKind
+}
+
+impl LedgerAccount {
+    //Incomes and expenses without a wallet are kept on their own asset account
+    pub fn wallet(account: Option<&Account>) -> Self {
+        match account {
+            Some(account) => LedgerAccount::new(format!("1.{}", account.get_id()), account.get_name(), LedgerKind::Asset),
+            None => LedgerAccount::new("1.0".to_string(), "Без счёта", LedgerKind::Asset)
+        }
+    }
+
+    //Contra account of fixed assets, it grows by the depreciation posted so far
+    pub fn accumulated_depreciation() -> Self {
+        LedgerAccount::new("2.0".to_string(), "Накопленная амортизация", LedgerKind::Asset)
+    }
+
+    pub fn opening_balances() -> Self {
+        LedgerAccount::new("3.0".to_string(), "Начальные остатки", LedgerKind::Equity)
+    }
+
+    //Clearing account for transfers between wallets in different currencies
+    pub fn currency_exchange() -> Self {
+        LedgerAccount::new("3.1".to_string(), "Обмен валют", LedgerKind::Equity)
+    }
+
+    pub fn revenue(category: &Category) -> Self {
+        LedgerAccount::new(format!("4.{}", category.get_id()), category.get_name(), LedgerKind::Revenue)
+    }
+
+    pub fn expense(category: &Category) -> Self {
+        LedgerAccount::new(format!("5.{}", category.get_id()), category.get_name(), LedgerKind::Expense)
+    }
+
+    //Overhead allocated to a cost center is moved here from the account of its category
+    pub fn cost_center(center: &CostCenter) -> Self {
+        LedgerAccount::new(format!("6.{}", center.get_id()), center.get_name(), LedgerKind::Expense)
+    }
+
+    fn new(code: String, name: &str, kind: LedgerKind) -> Self {
+        LedgerAccount {
+            ledger_code: code,
+            ledger_name: name.to_string(),
+            ledger_kind: kind
+        }
+    }
+
+    pub fn get_code(&self) -> &str {
+        &self.ledger_code
+    }
+
+    pub fn get_name(&self) -> &str {
+        &self.ledger_name
+    }
+
+    pub fn get_kind(&self) -> LedgerKind {
+        self.ledger_kind
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Posting {
+    account: LedgerAccount,
+    debit: Money,
+    credit: Money
+}
+
+impl Posting {
+    pub fn debit(account: LedgerAccount, amount: Money) -> Self {
+        Posting {
+            account,
+            debit: amount,
+            credit: Money::zero(amount.currency())
+        }
+    }
+
+    pub fn credit(account: LedgerAccount, amount: Money) -> Self {
+        Posting {
+            account,
+            debit: Money::zero(amount.currency()),
+            credit: amount
+        }
+    }
+
+    pub fn get_account(&self) -> &LedgerAccount {
+        &self.account
+    }
+
+    pub fn get_debit(&self) -> Money {
+        self.debit
+    }
+
+    pub fn get_credit(&self) -> Money {
+        self.credit
+    }
+
+    pub fn currency(&self) -> Currency {
+        self.debit.currency()
+    }
+}
+
+//Queries alias posting columns to `debit`, `credit`, `currency` and the ledger account to its field names
+impl<'r> FromRow<'r, MySqlRow> for Posting {
+    fn from_row(row: &'r MySqlRow) -> Result<Self, sqlx::Error> {
+        Ok(Posting {
+            account: LedgerAccount::from_row(row)?,
+            debit: Money::from_columns(row, "debit", "currency")?,
+            credit: Money::from_columns(row, "credit", "currency")?
+        })
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum LedgerError {
+    TooFewPostings,
+    //posting with both sides filled, none of them, a negative amount or different currencies
+    WrongPosting(String),
+    Imbalanced(Currency, Money),
+    Money(MoneyError)
+}
+
+impl Display for LedgerError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LedgerError::TooFewPostings => write!(f, "В проводке должно быть хотя бы два счёта"),
+            LedgerError::WrongPosting(code) => write!(f, "Неверная сумма по счёту {code}"),
+            LedgerError::Imbalanced(currency, difference) => write!(f, "Дебет и кредит в {currency} не сходятся на {difference}"),
+            LedgerError::Money(err) => write!(f, "{err}")
+        }
+    }
+}
+
+impl From<MoneyError> for LedgerError {
+    fn from(value: MoneyError) -> Self {
+        LedgerError::Money(value)
+    }
+}
+
+impl From<LedgerError> for QueryError {
+    fn from(_: LedgerError) -> Self {
+        QueryError::UnbalancedEntry
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum EntrySource {
+    Income,
+    Expense,
+    Transfer,
+    Opening,
+    //id of the source is the allocated period written as yyyymm
+    Allocation
+}
+
+impl EntrySource {
+    //Value stored in ENTRY_SOURCE column
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EntrySource::Income => "INCOME",
+            EntrySource::Expense => "EXPENSE",
+            EntrySource::Transfer => "TRANSFER",
+            EntrySource::Opening => "OPENING",
+            EntrySource::Allocation => "ALLOCATION"
+        }
+    }
+}
+
+//Entry can only be created balanced, so everything stored in the journal is balanced
+#[derive(Clone, Debug)]
+pub struct JournalEntry {
+    date: Date,
+    memo: String,
+    postings: Vec<Posting>
+}
+
+impl JournalEntry {
+    pub fn new(date: Date, memo: &str, postings: Vec<Posting>) -> Result<Self, LedgerError> {
+        if postings.len() < 2 {
+            return Err(LedgerError::TooFewPostings)
+        }
+
+        let mut differences: BTreeMap<Currency, Money> = BTreeMap::new();
+
+        for posting in &postings {
+            let one_sided = posting.debit.is_zero() != posting.credit.is_zero();
+            let same_currency = posting.debit.currency() == posting.credit.currency();
+
+            if !one_sided || !same_currency || posting.debit.is_negative() || posting.credit.is_negative() {
+                return Err(LedgerError::WrongPosting(posting.account.ledger_code.clone()))
+            }
+
+            let difference = differences.entry(posting.currency()).or_insert(Money::zero(posting.currency()));
+            *difference = difference.checked_add(posting.debit)?.checked_sub(posting.credit)?;
+        }
+
+        match differences.into_iter().find(|(_, difference)| !difference.is_zero()) {
+            Some((currency, difference)) => Err(LedgerError::Imbalanced(currency, difference)),
+            None => Ok(JournalEntry {
+                date,
+                memo: memo.to_string(),
+                postings
+            })
+        }
+    }
+
+    pub fn get_date(&self) -> Date {
+        self.date
+    }
+
+    pub fn get_memo(&self) -> &str {
+        &self.memo
+    }
+
+    pub fn get_postings(&self) -> &[Posting] {
+        &self.postings
+    }
+}
+
+//Negative amounts (refunds, overdrafts) are posted to the opposite sides
+fn pair(debit: LedgerAccount, credit: LedgerAccount, amount: Money) -> Result<Vec<Posting>, LedgerError> {
+    match amount.is_negative() {
+        false => Ok(vec![Posting::debit(debit, amount), Posting::credit(credit, amount)]),
+        true => {
+            let amount = amount.checked_neg()?;
+            Ok(vec![Posting::debit(credit, amount), Posting::credit(debit, amount)])
+        }
+    }
+}
+
+//Money comes to the wallet from the revenue account
+pub fn income_entry(date: Date, name: &str, price: Money, category: &Category, account: Option<&Account>) -> Result<JournalEntry, LedgerError> {
+    JournalEntry::new(date, name, pair(LedgerAccount::wallet(account), LedgerAccount::revenue(category), price)?)
+}
+
+//Money leaves the wallet to the expense account
+pub fn expense_entry(date: Date, name: &str, price: Money, category: &Category, account: Option<&Account>) -> Result<JournalEntry, LedgerError> {
+    JournalEntry::new(date, name, pair(LedgerAccount::expense(category), LedgerAccount::wallet(account), price)?)
+}
+
+//Depreciation lowers the value of the asset, no money leaves wallets
+pub fn depreciation_entry(date: Date, name: &str, price: Money, category: &Category) -> Result<JournalEntry, LedgerError> {
+    JournalEntry::new(date, name, pair(LedgerAccount::expense(category), LedgerAccount::accumulated_depreciation(), price)?)
+}
+
+//Wallets in different currencies (or amounts which differ by a fee) are balanced through the exchange account
+pub fn transfer_entry(sent: Money, received: Money, date: Date, from: &Account, to: &Account) -> Result<JournalEntry, LedgerError> {
+    let memo = format!("Перевод {} → {}", from.get_name(), to.get_name());
+
+    let postings = match sent == received {
+        true => pair(LedgerAccount::wallet(Some(to)), LedgerAccount::wallet(Some(from)), sent)?,
+        false => {
+            let mut postings = pair(LedgerAccount::wallet(Some(to)), LedgerAccount::currency_exchange(), received)?;
+            postings.extend(pair(LedgerAccount::currency_exchange(), LedgerAccount::wallet(Some(from)), sent)?);
+            postings
+        }
+    };
+
+    JournalEntry::new(date, &memo, postings)
+}
+
+//Wallets which start from zero have no opening entry
+pub fn opening_entry(account: &Account, date: Date) -> Result<Option<JournalEntry>, LedgerError> {
+    if account.get_opening().is_zero() {
+        return Ok(None)
+    }
+
+    let memo = format!("Начальный остаток {}", account.get_name());
+    JournalEntry::new(date, &memo, pair(LedgerAccount::wallet(Some(account)), LedgerAccount::opening_balances(), account.get_opening())?).map(Some)
+}
+
+//Overhead of categories is moved to cost centers, total expenses don't change
+pub fn allocation_entry<'a>(date: Date, memo: &str, allocations: impl IntoIterator<Item = (&'a Category, &'a CostCenter, Money)>) -> Result<JournalEntry, LedgerError> {
+    let mut postings = Vec::new();
+
+    for (category, center, amount) in allocations.into_iter().filter(|(_, _, amount)| !amount.is_zero()) {
+        postings.extend(pair(LedgerAccount::cost_center(center), LedgerAccount::expense(category), amount)?);
+    }
+
+    JournalEntry::new(date, memo, postings)
+}
+
+//Net balance of an account in one currency, put to the debit or credit column
+pub struct TrialBalanceRow {
+    pub account: LedgerAccount,
+    pub debit: Money,
+    pub credit: Money
+}
+
+pub fn trial_balance(postings: &[Posting]) -> Result<Vec<TrialBalanceRow>, MoneyError> {
+    let mut balances: BTreeMap<(LedgerAccount, Currency), Money> = BTreeMap::new();
+
+    for posting in postings {
+        let balance = balances.entry((posting.account.clone(), posting.currency())).or_insert(Money::zero(posting.currency()));
+        *balance = balance.checked_add(posting.debit)?.checked_sub(posting.credit)?;
+    }
+
+    balances
+        .into_iter()
+        .map(|((account, currency), balance)| Ok(match balance.is_negative() {
+            false => TrialBalanceRow {account, debit: balance, credit: Money::zero(currency)},
+            true => TrialBalanceRow {account, debit: Money::zero(currency), credit: balance.checked_neg()?}
+        }))
+        .collect()
+}
+
+//Sum of debit and credit columns for each currency, they are equal when the journal is balanced
+pub fn trial_balance_totals(rows: &[TrialBalanceRow]) -> Result<Vec<(Money, Money)>, MoneyError> {
+    let mut totals: BTreeMap<Currency, (Money, Money)> = BTreeMap::new();
+
+    for row in rows {
+        let currency = row.debit.currency();
+        let (debit, credit) = totals.entry(currency).or_insert((Money::zero(currency), Money::zero(currency)));
+        *debit = debit.checked_add(row.debit)?;
+        *credit = credit.checked_add(row.credit)?;
+    }
+
+    Ok(totals.into_values().collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use time::Month;
+
+    fn date() -> Date {
+        Date::from_calendar_date(2024, Month::May, 15).unwrap()
+    }
+
+    fn rub(minor: i64) -> Money {
+        Money::from_minor(minor, Currency::RUB)
+    }
+
+    fn usd(minor: i64) -> Money {
+        Money::from_minor(minor, Currency::USD)
+    }
+
+    fn wallet() -> LedgerAccount {
+        LedgerAccount::wallet(None)
+    }
+
+    #[test]
+    fn accepts_balanced_entry() {
+        let entry = JournalEntry::new(date(), "", vec![
+            Posting::debit(wallet(), rub(1_000)),
+            Posting::credit(LedgerAccount::opening_balances(), rub(600)),
+            Posting::credit(LedgerAccount::currency_exchange(), rub(400))
+        ]).unwrap();
+
+        assert_eq!(entry.get_postings().len(), 3);
+    }
+
+    #[test]
+    fn rejects_unbalanced_entry() {
+        let entry = JournalEntry::new(date(), "", vec![
+            Posting::debit(wallet(), rub(1_000)),
+            Posting::credit(LedgerAccount::opening_balances(), rub(999))
+        ]);
+
+        assert_eq!(entry.err(), Some(LedgerError::Imbalanced(Currency::RUB, rub(1))));
+    }
+
+    #[test]
+    fn every_currency_must_balance_on_its_own() {
+        let entry = JournalEntry::new(date(), "", vec![
+            Posting::debit(wallet(), rub(1_000)),
+            Posting::credit(LedgerAccount::currency_exchange(), usd(1_000))
+        ]);
+
+        assert!(matches!(entry, Err(LedgerError::Imbalanced(..))));
+    }
+
+    #[test]
+    fn rejects_malformed_postings() {
+        let single = JournalEntry::new(date(), "", vec![Posting::debit(wallet(), rub(1_000))]);
+        let negative = JournalEntry::new(date(), "", vec![
+            Posting::debit(wallet(), rub(-1_000)),
+            Posting::credit(LedgerAccount::opening_balances(), rub(-1_000))
+        ]);
+        let empty = JournalEntry::new(date(), "", vec![
+            Posting::debit(wallet(), rub(0)),
+            Posting::credit(LedgerAccount::opening_balances(), rub(0))
+        ]);
+
+        assert_eq!(single.err(), Some(LedgerError::TooFewPostings));
+        assert_eq!(negative.err(), Some(LedgerError::WrongPosting("1.0".to_string())));
+        assert_eq!(empty.err(), Some(LedgerError::WrongPosting("1.0".to_string())));
+    }
+
+    #[test]
+    fn refunds_are_posted_to_opposite_sides() {
+        let postings = pair(wallet(), LedgerAccount::opening_balances(), rub(-500)).unwrap();
+
+        assert_eq!(postings[0].get_account(), &LedgerAccount::opening_balances());
+        assert_eq!(postings[0].get_debit(), rub(500));
+        assert_eq!(postings[1].get_account(), &wallet());
+        assert_eq!(postings[1].get_credit(), rub(500));
+    }
+
+    #[test]
+    fn trial_balance_of_balanced_journal_is_even() {
+        let mut postings = pair(wallet(), LedgerAccount::opening_balances(), rub(1_000)).unwrap();
+        postings.extend(pair(LedgerAccount::currency_exchange(), wallet(), rub(300)).unwrap());
+
+        let rows = trial_balance(&postings).unwrap();
+        let totals = trial_balance_totals(&rows).unwrap();
+
+        assert_eq!(totals, vec![(rub(1_000), rub(1_000))]);
+    }
+}
@@ -0,0 +1,386 @@
+use std::fmt::{Display, Formatter};
+use rust_decimal::Decimal;
+use rust_decimal::prelude::ToPrimitive;
+use sqlx::{Decode, Encode, FromRow, MySql, Row, Type};
+use sqlx::encode::IsNull;
+use sqlx::error::BoxDynError;
+use sqlx::mysql::{MySqlRow, MySqlTypeInfo, MySqlValueRef};
+
+//All supported currencies have two digits after the point (kopecks, cents...)
+const MINOR_IN_MAJOR: i64 = 100;
+const MINOR_DIGITS: u32 = 2;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Currency([u8; 3]);
+
+impl Currency {
+    pub const RUB: Currency = Currency(*b"RUB");
+    pub const USD: Currency = Currency(*b"USD");
+    pub const EUR: Currency = Currency(*b"EUR");
+
+    //Currencies offered in the app, all of them have two minor digits
+    pub const SUPPORTED: [Currency; 7] = [
+        Currency::RUB,
+        Currency::USD,
+        Currency::EUR,
+        Currency(*b"CNY"),
+        Currency(*b"KZT"),
+        Currency(*b"BYN"),
+        Currency(*b"GBP")
+    ];
+
+    //ISO 4217 code, three latin letters
+    pub fn from_code(code: &str) -> Option<Currency> {
+        let code = code.trim().to_ascii_uppercase();
+
+        match <[u8; 3]>::try_from(code.as_bytes()) {
+            Ok(bytes) if bytes.iter().all(|byte| byte.is_ascii_uppercase()) => Some(Currency(bytes)),
+            _ => None
+        }
+    }
+
+    pub fn code(&self) -> &str {
+        std::str::from_utf8(&self.0).unwrap_or("???")
+    }
+
+    pub fn symbol(&self) -> &str {
+        match &self.0 {
+            b"RUB" => "₽",
+            b"USD" => "$",
+            b"EUR" => "€",
+            _ => self.code()
+        }
+    }
+}
+
+impl Default for Currency {
+    fn default() -> Self {
+        Currency::RUB
+    }
+}
+
+impl Display for Currency {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.code())
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Default)]
+pub enum Locale {
+    //1 234,56 ₽
+    #[default]
+    Ru,
+    //₽1,234.56
+    En
+}
+
+impl Locale {
+    fn group_separator(&self) -> char {
+        match self {
+            Locale::Ru => ' ',
+            Locale::En => ','
+        }
+    }
+
+    fn decimal_separator(&self) -> char {
+        match self {
+            Locale::Ru => ',',
+            Locale::En => '.'
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum MoneyError {
+    CurrencyMismatch(Currency, Currency),
+    Overflow,
+    InvalidFormat
+}
+
+impl Display for MoneyError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MoneyError::CurrencyMismatch(left, right) => write!(f, "Нельзя сложить {left} и {right}"),
+            MoneyError::Overflow => write!(f, "Слишком большая сумма"),
+            MoneyError::InvalidFormat => write!(f, "Неверный формат суммы")
+        }
+    }
+}
+
+impl std::error::Error for MoneyError {}
+
+//Exact amount of money stored in minor units (kopecks, cents) together with its currency
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Money {
+    minor: i64,
+    currency: Currency
+}
+
+impl Money {
+    pub fn from_minor(minor: i64, currency: Currency) -> Self {
+        Money {
+            minor,
+            currency
+        }
+    }
+
+    pub fn zero(currency: Currency) -> Self {
+        Money::from_minor(0, currency)
+    }
+
+    pub fn minor(&self) -> i64 {
+        self.minor
+    }
+
+    pub fn currency(&self) -> Currency {
+        self.currency
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.minor == 0
+    }
+
+    pub fn is_negative(&self) -> bool {
+        self.minor < 0
+    }
+
+    pub fn checked_add(self, other: Money) -> Result<Money, MoneyError> {
+        if self.currency != other.currency {
+            return Err(MoneyError::CurrencyMismatch(self.currency, other.currency))
+        }
+
+        self.minor
+            .checked_add(other.minor)
+            .map(|minor| Money::from_minor(minor, self.currency))
+            .ok_or(MoneyError::Overflow)
+    }
+
+    pub fn checked_sub(self, other: Money) -> Result<Money, MoneyError> {
+        self.checked_add(other.checked_neg()?)
+    }
+
+    pub fn checked_neg(self) -> Result<Money, MoneyError> {
+        self.minor
+            .checked_neg()
+            .map(|minor| Money::from_minor(minor, self.currency))
+            .ok_or(MoneyError::Overflow)
+    }
+
+    //Sum of an empty list is zero in the given currency
+    pub fn sum(amounts: impl IntoIterator<Item = Money>, currency: Currency) -> Result<Money, MoneyError> {
+        amounts
+            .into_iter()
+            .try_fold(Money::zero(currency), |total, amount| total.checked_add(amount))
+    }
+
+    pub fn to_decimal(self) -> Decimal {
+        Decimal::new(self.minor, MINOR_DIGITS)
+    }
+
+    //Amounts with fractions of a minor unit are rejected instead of being rounded
+    pub fn from_decimal(amount: Decimal, currency: Currency) -> Result<Money, MoneyError> {
+        let minor = amount
+            .checked_mul(Decimal::from(MINOR_IN_MAJOR))
+            .ok_or(MoneyError::Overflow)?;
+
+        if !minor.fract().is_zero() {
+            return Err(MoneyError::InvalidFormat)
+        }
+
+        minor
+            .to_i64()
+            .map(|minor| Money::from_minor(minor, currency))
+            .ok_or(MoneyError::Overflow)
+    }
+
+    //Accepts optional sign, group separators of the locale and up to two digits after the decimal separator.
+    //Point is always accepted as decimal separator, because it is never used for grouping in Ru locale.
+    pub fn parse(input: &str, currency: Currency, locale: Locale) -> Result<Money, MoneyError> {
+        let input = input.trim();
+
+        let (is_negative, unsigned) = match input.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, input.strip_prefix('+').unwrap_or(input))
+        };
+
+        let cleaned: String = unsigned
+            .chars()
+            .filter(|c| *c != locale.group_separator() && *c != ' ' && *c != '\u{a0}' && *c != '\u{202f}')
+            .map(|c| if c == locale.decimal_separator() {'.'} else {c})
+            .collect();
+
+        let (integer, fraction) = match cleaned.split_once('.') {
+            Some((integer, fraction)) => (integer, fraction),
+            None => (cleaned.as_str(), "")
+        };
+
+        let is_digits = |part: &str| part.chars().all(|c| c.is_ascii_digit());
+
+        if integer.is_empty() || !is_digits(integer) || !is_digits(fraction) || fraction.len() > MINOR_DIGITS as usize {
+            return Err(MoneyError::InvalidFormat)
+        }
+
+        let integer: i64 = integer.parse().map_err(|_| MoneyError::Overflow)?;
+        let fraction: i64 = format!("{fraction:0<2}").parse().map_err(|_| MoneyError::InvalidFormat)?;
+
+        let minor = integer
+            .checked_mul(MINOR_IN_MAJOR)
+            .and_then(|minor| minor.checked_add(fraction))
+            .ok_or(MoneyError::Overflow)?;
+
+        Ok(Money::from_minor(if is_negative {-minor} else {minor}, currency))
+    }
+
+    //Number without grouping and currency, suitable to be put back into an input
+    pub fn to_input_string(self, locale: Locale) -> String {
+        let sign = if self.is_negative() {"-"} else {""};
+        let major = self.minor.unsigned_abs() / MINOR_IN_MAJOR as u64;
+        let minor = self.minor.unsigned_abs() % MINOR_IN_MAJOR as u64;
+
+        format!("{sign}{major}{}{minor:02}", locale.decimal_separator())
+    }
+
+    pub fn format(&self, locale: Locale) -> String {
+        let sign = if self.is_negative() {"-"} else {""};
+        let major = (self.minor.unsigned_abs() / MINOR_IN_MAJOR as u64).to_string();
+        let minor = self.minor.unsigned_abs() % MINOR_IN_MAJOR as u64;
+
+        let mut grouped = String::new();
+        for (index, digit) in major.chars().enumerate() {
+            if index > 0 && (major.len() - index).is_multiple_of(3) {
+                grouped.push(locale.group_separator());
+            }
+            grouped.push(digit);
+        }
+
+        let number = format!("{grouped}{}{minor:02}", locale.decimal_separator());
+
+        match locale {
+            Locale::Ru => format!("{sign}{number} {}", self.currency.symbol()),
+            Locale::En => format!("{sign}{}{number}", self.currency.symbol())
+        }
+    }
+}
+
+impl Display for Money {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.format(Locale::default()))
+    }
+}
+
+impl Type<MySql> for Currency {
+    fn type_info() -> MySqlTypeInfo {
+        <str as Type<MySql>>::type_info()
+    }
+
+    fn compatible(ty: &MySqlTypeInfo) -> bool {
+        <str as Type<MySql>>::compatible(ty)
+    }
+}
+
+impl<'r> Decode<'r, MySql> for Currency {
+    fn decode(value: MySqlValueRef<'r>) -> Result<Self, BoxDynError> {
+        let code = <&str as Decode<MySql>>::decode(value)?;
+
+        Currency::from_code(code).ok_or_else(|| format!("unknown currency code: {code}").into())
+    }
+}
+
+impl<'q> Encode<'q, MySql> for Currency {
+    fn encode_by_ref(&self, buf: &mut Vec<u8>) -> IsNull {
+        <&str as Encode<MySql>>::encode(self.code(), buf)
+    }
+}
+
+//Amount lives in a DECIMAL column next to a CHAR(3) currency column,
+//so queries alias them to `price` and `currency` and structs use #[sqlx(flatten)]
+impl<'r> FromRow<'r, MySqlRow> for Money {
+    fn from_row(row: &'r MySqlRow) -> Result<Self, sqlx::Error> {
+        Money::from_columns(row, "price", "currency")
+    }
+}
+
+impl Money {
+    //For rows with several amounts, each of them next to its own currency column
+    pub fn from_columns(row: &MySqlRow, amount_column: &str, currency_column: &str) -> Result<Money, sqlx::Error> {
+        let amount: Decimal = row.try_get(amount_column)?;
+        let currency: Currency = row.try_get(currency_column)?;
+
+        Money::from_decimal(amount, currency).map_err(|err| sqlx::Error::ColumnDecode {
+            index: amount_column.to_string(),
+            source: Box::new(err)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rub(minor: i64) -> Money {
+        Money::from_minor(minor, Currency::RUB)
+    }
+
+    #[test]
+    fn checked_arithmetic() {
+        assert_eq!(rub(150).checked_add(rub(-200)), Ok(rub(-50)));
+        assert_eq!(rub(150).checked_sub(rub(200)), Ok(rub(-50)));
+        assert_eq!(rub(150).checked_neg(), Ok(rub(-150)));
+        assert_eq!(Money::sum([rub(1), rub(2), rub(3)], Currency::RUB), Ok(rub(6)));
+        assert_eq!(Money::sum([], Currency::USD), Ok(Money::zero(Currency::USD)));
+    }
+
+    #[test]
+    fn checked_arithmetic_fails_instead_of_wrapping() {
+        assert_eq!(rub(i64::MAX).checked_add(rub(1)), Err(MoneyError::Overflow));
+        assert_eq!(rub(i64::MIN).checked_neg(), Err(MoneyError::Overflow));
+        assert_eq!(rub(i64::MIN).checked_sub(rub(1)), Err(MoneyError::Overflow));
+        assert_eq!(
+            rub(100).checked_add(Money::from_minor(100, Currency::USD)),
+            Err(MoneyError::CurrencyMismatch(Currency::RUB, Currency::USD))
+        );
+    }
+
+    #[test]
+    fn parses_ru_amounts() {
+        let parse = |input: &str| Money::parse(input, Currency::RUB, Locale::Ru);
+
+        assert_eq!(parse("1 234,56"), Ok(rub(123456)));
+        assert_eq!(parse("1\u{a0}234,5"), Ok(rub(123450)));
+        assert_eq!(parse("1234.56"), Ok(rub(123456)));
+        assert_eq!(parse(" -0,01 "), Ok(rub(-1)));
+        assert_eq!(parse("+7"), Ok(rub(700)));
+    }
+
+    #[test]
+    fn parses_en_amounts() {
+        let parse = |input: &str| Money::parse(input, Currency::USD, Locale::En);
+
+        assert_eq!(parse("1,234.56"), Ok(Money::from_minor(123456, Currency::USD)));
+        assert_eq!(parse("-12.3"), Ok(Money::from_minor(-1230, Currency::USD)));
+        assert_eq!(parse("1 000"), Ok(Money::from_minor(100000, Currency::USD)));
+    }
+
+    #[test]
+    fn rejects_malformed_amounts() {
+        for input in ["", "-", ",5", "1,234", "1.2.3", "12abc", "1,001"] {
+            assert_eq!(Money::parse(input, Currency::RUB, Locale::Ru), Err(MoneyError::InvalidFormat), "{input}");
+        }
+
+        assert_eq!(Money::parse("92233720368547758.08", Currency::RUB, Locale::Ru), Err(MoneyError::Overflow));
+    }
+
+    #[test]
+    fn decimal_round_trip_keeps_minor_units() {
+        assert_eq!(rub(-12345).to_decimal(), Decimal::new(-12345, 2));
+        assert_eq!(Money::from_decimal(Decimal::new(-12345, 2), Currency::RUB), Ok(rub(-12345)));
+        assert_eq!(Money::from_decimal(Decimal::new(1, 3), Currency::RUB), Err(MoneyError::InvalidFormat));
+    }
+
+    #[test]
+    fn formats_by_locale() {
+        assert_eq!(rub(-123456789).format(Locale::Ru), "-1 234 567,89 ₽");
+        assert_eq!(Money::from_minor(123456, Currency::USD).format(Locale::En), "$1,234.56");
+        assert_eq!(rub(-5).to_input_string(Locale::Ru), "-0,05");
+    }
+}
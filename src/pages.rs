@@ -6,7 +6,7 @@ pub mod notes_page;
 pub enum Page {
     LoginPage(Login),
     RegistrationPage(Login),
-    NotesPage(Notes)
+    NotesPage(Box<Notes>)
 }
 
 
@@ -1,48 +1,1117 @@
 
+use std::str::FromStr;
+use rust_decimal::Decimal;
+use time::{Date, Month};
+use crate::income::Income;
+use crate::category::{Category, CategoryKind, parse_color};
+use crate::exchange::RateDraft;
+use crate::budget::{BudgetDraft, current_month};
+use crate::recurring::{Period, TemplateDraft};
+use crate::account::{Account, AccountDraft, TransferDraft};
+use crate::exchange::{ExchangeRate, convert};
+use crate::money::{Currency, Locale, Money};
+use crate::cost_center::{CostCenter, ExpenseSplit, Share, ShareKind, SplitDraft, fits};
+use crate::expense::Expense;
+use crate::allocation::{Driver, DriverDraft, RuleDraft};
+use crate::job::{Job, JobDraft, JobStatus};
+use crate::inventory::{CostMethod, Issue, IssueDraft, Item, ItemDraft, Lot, LotDraft, issue_fits};
+use crate::standard::{CostObject, CostObjectKind, StandardDraft};
+use crate::breakeven::WhatIf;
+use crate::asset::{AssetDraft, DepreciationMethod};
+
 pub struct Notes {
     pub current_category: NotesCategory,
     pub show_modal: bool,
+    pub incomes_sort: TableSort,
+    //currency in which totals of incomes and expenses are shown
+    pub reporting_currency: Currency,
     //inputs for creating new incomes and expenses...
     pub name_input: String,
     pub price_input: String,
-    pub category_input: String,
+    pub category_input: Option<Category>,
     pub date_input: String,
+    pub currency_input: Currency,
+    //note without an account doesn't change any balance
+    pub account_input: Option<Account>,
+    //note without a job isn't counted in profitability of any job
+    pub job_input: Option<Job>,
+    pub note_name_error: Option<InputError>,
+    pub price_input_error: Option<InputError>,
+    pub category_input_error: Option<InputError>,
+    pub date_input_error: Option<InputError>,
+    //id of income or expense (depends on current category) which is edited through the form
+    pub editing_note: Option<i32>,
+    //id of income or expense waiting for confirmation of deletion
+    pub pending_deletion: Option<i32>,
+
+    //inputs for creating new categories...
+    pub new_category_name: String,
+    pub new_category_kind: CategoryKind,
+    pub new_category_color: String,
+    pub new_category_icon: String,
+    pub new_category_parent: Option<Category>,
+    pub new_category_name_error: Option<InputError>,
+    pub new_category_color_error: Option<InputError>,
+
+    //inputs for adding exchange rates...
+    pub rate_from_input: Currency,
+    pub rate_to_input: Currency,
+    pub rate_value_input: String,
+    pub rate_date_input: String,
+    pub rate_value_error: Option<InputError>,
+    pub rate_date_error: Option<InputError>,
+    pub rates_csv_path: String,
+    pub rates_import_result: Option<String>,
+
+    //inputs for setting budgets...
+    pub budget_category_input: Option<Category>,
+    pub budget_limit_input: String,
+    pub budget_currency_input: Currency,
+    pub budget_category_error: Option<InputError>,
+    pub budget_limit_error: Option<InputError>,
+    //month shown on the budgets page
+    pub budget_month: (i32, Month),
+    //shown when the last added or edited expense went over a budget
+    pub budget_warning: Option<String>,
+
+    //inputs for recurring templates, other fields are shared with the income/expense form
+    //(date input is the start of the template)...
+    pub template_kind_input: CategoryKind,
+    pub template_period_input: Period,
+    pub template_end_input: String,
+    pub template_end_error: Option<InputError>,
+
+    //inputs for adding accounts...
+    pub new_account_name: String,
+    pub new_account_currency: Currency,
+    pub new_account_opening: String,
+    pub new_account_name_error: Option<InputError>,
+    pub new_account_opening_error: Option<InputError>,
+
+    //inputs for transfers between accounts...
+    pub transfer_from_input: Option<Account>,
+    pub transfer_to_input: Option<Account>,
+    pub transfer_sent_input: String,
+    //may stay empty if both accounts have the same currency or there is a rate on the date
+    pub transfer_received_input: String,
+    pub transfer_date_input: String,
+    pub transfer_accounts_error: Option<InputError>,
+    pub transfer_sent_error: Option<InputError>,
+    pub transfer_received_error: Option<InputError>,
+    pub transfer_date_error: Option<InputError>,
+
+    //inputs for cost centers and splitting expenses between them...
+    pub new_center_name: String,
+    pub new_center_name_error: Option<InputError>,
+    //expense which is split on the cost centers page
+    pub split_expense: Option<i32>,
+    pub split_center_input: Option<CostCenter>,
+    pub split_kind_input: ShareKind,
+    pub split_value_input: String,
+    pub split_center_error: Option<InputError>,
+    pub split_value_error: Option<InputError>,
+    //shown when the split doesn't fit into the expense
+    pub split_warning: Option<String>,
+    //date range of the cost centers report, empty input means unbounded
+    pub report_from_input: String,
+    pub report_to_input: String,
+
+    //inputs for overhead allocation rules and drivers of cost centers...
+    pub rule_category_input: Option<Category>,
+    pub rule_driver_input: Driver,
+    pub rule_category_error: Option<InputError>,
+    pub driver_center_input: Option<CostCenter>,
+    pub driver_kind_input: Driver,
+    pub driver_value_input: String,
+    pub driver_center_error: Option<InputError>,
+    pub driver_value_error: Option<InputError>,
+    //month shown and allocated on the overhead page
+    pub allocation_month: (i32, Month),
+    //shown when overhead of the month can't be allocated
+    pub allocation_warning: Option<String>,
+
+    //inputs for adding jobs, end may stay empty...
+    pub new_job_name: String,
+    pub new_job_client: String,
+    pub new_job_start: String,
+    pub new_job_end: String,
+    pub new_job_status: JobStatus,
+    pub new_job_center: Option<CostCenter>,
+    pub new_job_name_error: Option<InputError>,
+    pub new_job_start_error: Option<InputError>,
+    pub new_job_end_error: Option<InputError>,
+
+    //inputs for inventory items, their purchases and issues...
+    pub new_item_name: String,
+    pub new_item_unit: String,
+    pub new_item_method: CostMethod,
+    pub new_item_currency: Currency,
+    pub new_item_name_error: Option<InputError>,
+    pub new_item_unit_error: Option<InputError>,
+    //item which lots and issues are shown on the inventory page
+    pub inventory_item: Option<i32>,
+    pub lot_date_input: String,
+    pub lot_quantity_input: String,
+    //in the currency of the item
+    pub lot_cost_input: String,
+    pub lot_date_error: Option<InputError>,
+    pub lot_quantity_error: Option<InputError>,
+    pub lot_cost_error: Option<InputError>,
+    pub issue_date_input: String,
+    pub issue_quantity_input: String,
+    pub issue_date_error: Option<InputError>,
+    pub issue_quantity_error: Option<InputError>,
+    //shown when the issue is more than there is in stock
+    pub inventory_warning: Option<String>,
+    //period of the valuation report, empty input means unbounded
+    pub inventory_from_input: String,
+    pub inventory_to_input: String,
+
+    //inputs for standard costs, either an item or a category is used depending on the kind...
+    pub standard_kind_input: CostObjectKind,
+    pub standard_item_input: Option<Item>,
+    pub standard_category_input: Option<Category>,
+    pub standard_quantity_input: String,
+    pub standard_price_input: String,
+    pub standard_currency_input: Currency,
+    pub standard_object_error: Option<InputError>,
+    pub standard_quantity_error: Option<InputError>,
+    pub standard_price_error: Option<InputError>,
+    //month of the variance report
+    pub standard_month: (i32, Month),
+
+    //period of the break-even analysis, empty input means unbounded
+    pub breakeven_from_input: String,
+    pub breakeven_to_input: String,
+    //set by the sliders, not saved anywhere
+    pub what_if: WhatIf,
+
+    //inputs for fixed assets, expected units are needed only for units-of-production...
+    pub asset_name_input: String,
+    pub asset_cost_input: String,
+    //empty means nothing is left at the end of the life
+    pub asset_salvage_input: String,
+    pub asset_currency_input: Currency,
+    pub asset_acquired_input: String,
+    //in months
+    pub asset_life_input: String,
+    pub asset_method_input: DepreciationMethod,
+    pub asset_units_input: String,
+    pub asset_category_input: Option<Category>,
+    pub asset_name_error: Option<InputError>,
+    pub asset_cost_error: Option<InputError>,
+    pub asset_salvage_error: Option<InputError>,
+    pub asset_acquired_error: Option<InputError>,
+    pub asset_life_error: Option<InputError>,
+    pub asset_units_error: Option<InputError>,
+    pub asset_category_error: Option<InputError>,
+    //asset which schedule is shown
+    pub schedule_asset: Option<i32>,
+    pub usage_month_input: String,
+    pub usage_units_input: String,
+    pub usage_month_error: Option<InputError>,
+    pub usage_units_error: Option<InputError>,
+    //shown when depreciation of the month was already posted
+    pub usage_warning: Option<String>,
+
+    //months of the cash-flow forecast after the current one
+    pub forecast_horizon: u32,
+
+    //kind of the notes the dashboard pie is split by
+    pub dashboard_kind: CategoryKind,
 
     //inputs for changing profile info...
     pub profile_name_input: String,
     pub profile_surname_input: String,
     pub name_input_error: Option<InputError>,
     pub surname_input_error: Option<InputError>,
+    pub name_saved: bool,
+    pub surname_saved: bool,
 }
 
 impl Notes {
     pub fn new() -> Self {
         Notes {
-            current_category: NotesCategory::MyProfile,
+            current_category: NotesCategory::Dashboard,
             show_modal: false,
+            incomes_sort: TableSort::default(),
+            reporting_currency: Currency::default(),
             name_input: String::new(),
             price_input: String::new(),
-            category_input: String::new(),
+            category_input: None,
             date_input: String::new(),
+            currency_input: Currency::default(),
+            account_input: None,
+            job_input: None,
+            note_name_error: None,
+            price_input_error: None,
+            category_input_error: None,
+            date_input_error: None,
+            editing_note: None,
+            pending_deletion: None,
+            new_category_name: String::new(),
+            new_category_kind: CategoryKind::Expense,
+            new_category_color: String::from("#BD93F9"),
+            new_category_icon: String::new(),
+            new_category_parent: None,
+            new_category_name_error: None,
+            new_category_color_error: None,
+            rate_from_input: Currency::USD,
+            rate_to_input: Currency::RUB,
+            rate_value_input: String::new(),
+            rate_date_input: String::new(),
+            rate_value_error: None,
+            rate_date_error: None,
+            rates_csv_path: String::new(),
+            rates_import_result: None,
+            budget_category_input: None,
+            budget_limit_input: String::new(),
+            budget_currency_input: Currency::default(),
+            budget_category_error: None,
+            budget_limit_error: None,
+            budget_month: current_month(),
+            budget_warning: None,
+            template_kind_input: CategoryKind::Expense,
+            template_period_input: Period::Monthly,
+            template_end_input: String::new(),
+            template_end_error: None,
+            new_account_name: String::new(),
+            new_account_currency: Currency::default(),
+            new_account_opening: String::new(),
+            new_account_name_error: None,
+            new_account_opening_error: None,
+            transfer_from_input: None,
+            transfer_to_input: None,
+            transfer_sent_input: String::new(),
+            transfer_received_input: String::new(),
+            transfer_date_input: String::new(),
+            transfer_accounts_error: None,
+            transfer_sent_error: None,
+            transfer_received_error: None,
+            transfer_date_error: None,
+            new_center_name: String::new(),
+            new_center_name_error: None,
+            split_expense: None,
+            split_center_input: None,
+            split_kind_input: ShareKind::Percent,
+            split_value_input: String::new(),
+            split_center_error: None,
+            split_value_error: None,
+            split_warning: None,
+            report_from_input: String::new(),
+            report_to_input: String::new(),
+            rule_category_input: None,
+            rule_driver_input: Driver::Headcount,
+            rule_category_error: None,
+            driver_center_input: None,
+            driver_kind_input: Driver::Headcount,
+            driver_value_input: String::new(),
+            driver_center_error: None,
+            driver_value_error: None,
+            allocation_month: current_month(),
+            allocation_warning: None,
+            new_job_name: String::new(),
+            new_job_client: String::new(),
+            new_job_start: String::new(),
+            new_job_end: String::new(),
+            new_job_status: JobStatus::Active,
+            new_job_center: None,
+            new_job_name_error: None,
+            new_job_start_error: None,
+            new_job_end_error: None,
+            new_item_name: String::new(),
+            new_item_unit: String::new(),
+            new_item_method: CostMethod::Fifo,
+            new_item_currency: Currency::default(),
+            new_item_name_error: None,
+            new_item_unit_error: None,
+            inventory_item: None,
+            lot_date_input: String::new(),
+            lot_quantity_input: String::new(),
+            lot_cost_input: String::new(),
+            lot_date_error: None,
+            lot_quantity_error: None,
+            lot_cost_error: None,
+            issue_date_input: String::new(),
+            issue_quantity_input: String::new(),
+            issue_date_error: None,
+            issue_quantity_error: None,
+            inventory_warning: None,
+            inventory_from_input: String::new(),
+            inventory_to_input: String::new(),
+            standard_kind_input: CostObjectKind::Category,
+            standard_item_input: None,
+            standard_category_input: None,
+            standard_quantity_input: String::new(),
+            standard_price_input: String::new(),
+            standard_currency_input: Currency::default(),
+            standard_object_error: None,
+            standard_quantity_error: None,
+            standard_price_error: None,
+            standard_month: current_month(),
+            breakeven_from_input: String::new(),
+            breakeven_to_input: String::new(),
+            what_if: WhatIf::default(),
+            asset_name_input: String::new(),
+            asset_cost_input: String::new(),
+            asset_salvage_input: String::new(),
+            asset_currency_input: Currency::default(),
+            asset_acquired_input: String::new(),
+            asset_life_input: String::new(),
+            asset_method_input: DepreciationMethod::StraightLine,
+            asset_units_input: String::new(),
+            asset_category_input: None,
+            asset_name_error: None,
+            asset_cost_error: None,
+            asset_salvage_error: None,
+            asset_acquired_error: None,
+            asset_life_error: None,
+            asset_units_error: None,
+            asset_category_error: None,
+            schedule_asset: None,
+            usage_month_input: String::new(),
+            usage_units_input: String::new(),
+            usage_month_error: None,
+            usage_units_error: None,
+            usage_warning: None,
+            forecast_horizon: 6,
+            dashboard_kind: CategoryKind::Expense,
             profile_name_input: String::new(),
             profile_surname_input: String::new(),
             name_input_error: None,
-            surname_input_error: None
+            surname_input_error: None,
+            name_saved: false,
+            surname_saved: false
+        }
+    }
+
+    pub fn clear_note_inputs(&mut self) {
+        self.name_input.clear();
+        self.price_input.clear();
+        self.category_input = None;
+        self.date_input.clear();
+        self.currency_input = Currency::default();
+        self.account_input = None;
+        self.job_input = None;
+        self.note_name_error = None;
+        self.price_input_error = None;
+        self.category_input_error = None;
+        self.date_input_error = None;
+        self.editing_note = None;
+        self.template_end_input.clear();
+        self.template_end_error = None;
+    }
+
+    pub fn start_editing(&mut self, id: i32, name: &str, price: Money, category: Option<Category>, account: Option<Account>, date: String) {
+        self.clear_note_inputs();
+        self.editing_note = Some(id);
+        self.name_input = name.to_string();
+        self.price_input = price.to_input_string(Locale::default());
+        self.currency_input = price.currency();
+        self.category_input = category;
+        self.account_input = account;
+        self.date_input = date;
+    }
+
+    //Validates every input of the income/expense form, marks the wrong ones and returns the draft if all are correct
+    pub fn validate_note_form(&mut self) -> Option<NoteDraft> {
+        let name = self.name_input.trim().to_string();
+        let price = parse_price(&self.price_input, self.currency_input);
+        let category = self.category_input.as_ref().map(|category| category.get_id());
+        let date = parse_date(&self.date_input);
+
+        self.note_name_error = if name.is_empty() {Some(InputError::IncorrectFormat)} else {None};
+        self.price_input_error = price.map_or(Some(InputError::IncorrectFormat), |_| None);
+        self.category_input_error = category.map_or(Some(InputError::IncorrectFormat), |_| None);
+        self.date_input_error = date.map_or(Some(InputError::IncorrectFormat), |_| None);
+
+        match (name.is_empty(), price, category, date) {
+            (false, Some(price), Some(category), Some(date)) => Some(NoteDraft {
+                name,
+                price,
+                category,
+                account: self.account_input.as_ref().map(|account| account.get_id()),
+                job: self.job_input.as_ref().map(|job| job.get_id()),
+                date
+            }),
+            _ => None
+        }
+    }
+
+    //Template has no end when the end input is empty, otherwise the end can't be before the start
+    pub fn validate_template_form(&mut self) -> Option<TemplateDraft> {
+        let note = self.validate_note_form();
+        let end = match self.template_end_input.trim() {
+            "" => Some(None),
+            input => parse_date(input).map(Some)
+        };
+
+        self.template_end_error = match (&note, end) {
+            (_, None) => Some(InputError::IncorrectFormat),
+            (Some(note), Some(Some(end))) if end < note.date => Some(InputError::IncorrectFormat),
+            _ => None
+        };
+
+        match (note, end, self.template_end_error.as_ref()) {
+            (Some(note), Some(end), None) => Some(TemplateDraft {
+                kind: self.template_kind_input,
+                name: note.name,
+                price: note.price,
+                category: note.category,
+                period: self.template_period_input,
+                start: note.date,
+                end
+            }),
+            _ => None
+        }
+    }
+
+    pub fn validate_rate_form(&mut self) -> Option<RateDraft> {
+        let value = parse_rate(&self.rate_value_input);
+        let date = parse_date(&self.rate_date_input);
+
+        self.rate_value_error = value.map_or(Some(InputError::IncorrectFormat), |_| None);
+        self.rate_date_error = date.map_or(Some(InputError::IncorrectFormat), |_| None);
+
+        match (value, date) {
+            (Some(value), Some(date)) if self.rate_from_input != self.rate_to_input => Some(RateDraft {
+                from: self.rate_from_input,
+                to: self.rate_to_input,
+                value,
+                date
+            }),
+            _ => None
+        }
+    }
+
+    pub fn clear_budget_inputs(&mut self) {
+        self.budget_category_input = None;
+        self.budget_limit_input.clear();
+        self.budget_category_error = None;
+        self.budget_limit_error = None;
+    }
+
+    pub fn validate_budget_form(&mut self) -> Option<BudgetDraft> {
+        let category = self.budget_category_input.as_ref().map(|category| category.get_id());
+        let limit = parse_price(&self.budget_limit_input, self.budget_currency_input).filter(|limit| !limit.is_negative());
+
+        self.budget_category_error = category.map_or(Some(InputError::IncorrectFormat), |_| None);
+        self.budget_limit_error = limit.map_or(Some(InputError::IncorrectFormat), |_| None);
+
+        match (category, limit) {
+            (Some(category), Some(limit)) => Some(BudgetDraft {category, limit}),
+            _ => None
+        }
+    }
+
+    pub fn clear_account_inputs(&mut self) {
+        self.new_account_name.clear();
+        self.new_account_opening.clear();
+        self.new_account_name_error = None;
+        self.new_account_opening_error = None;
+    }
+
+    //Empty opening balance means the account starts from zero
+    pub fn validate_account_form(&mut self) -> Option<AccountDraft> {
+        let name = self.new_account_name.trim().to_string();
+        let opening = match self.new_account_opening.trim() {
+            "" => Some(Money::zero(self.new_account_currency)),
+            input => Money::parse(input, self.new_account_currency, Locale::default()).ok().filter(fits_amount)
+        };
+
+        self.new_account_name_error = if name.is_empty() {Some(InputError::IncorrectFormat)} else {None};
+        self.new_account_opening_error = opening.map_or(Some(InputError::IncorrectFormat), |_| None);
+
+        match (name.is_empty(), opening) {
+            (false, Some(opening)) => Some(AccountDraft {name, opening}),
+            _ => None
+        }
+    }
+
+    pub fn clear_transfer_inputs(&mut self) {
+        self.transfer_sent_input.clear();
+        self.transfer_received_input.clear();
+        self.transfer_date_input.clear();
+        self.transfer_accounts_error = None;
+        self.transfer_sent_error = None;
+        self.transfer_received_error = None;
+        self.transfer_date_error = None;
+    }
+
+    //Received amount is taken from the input, otherwise it's the sent one converted with the rate on the date
+    pub fn validate_transfer_form(&mut self, rates: &[ExchangeRate]) -> Option<TransferDraft> {
+        let accounts = match (&self.transfer_from_input, &self.transfer_to_input) {
+            (Some(from), Some(to)) if from.get_id() != to.get_id() => Some((from.get_id(), to.get_id(), to.get_currency())),
+            _ => None
+        };
+        let sent = self.transfer_from_input
+            .as_ref()
+            .and_then(|from| parse_price(&self.transfer_sent_input, from.get_currency()))
+            .filter(|sent| !sent.is_negative());
+        let date = parse_date(&self.transfer_date_input);
+
+        let received = match (accounts, sent, date, self.transfer_received_input.trim()) {
+            (Some((_, _, currency)), Some(sent), Some(date), "") => convert(rates, sent, currency, date).ok(),
+            (Some((_, _, currency)), _, _, input) => parse_price(input, currency).filter(|received| !received.is_negative()),
+            _ => None
+        };
+
+        self.transfer_accounts_error = accounts.map_or(Some(InputError::IncorrectFormat), |_| None);
+        self.transfer_sent_error = sent.map_or(Some(InputError::IncorrectFormat), |_| None);
+        self.transfer_date_error = date.map_or(Some(InputError::IncorrectFormat), |_| None);
+        self.transfer_received_error = match (accounts, sent, date) {
+            (Some(_), Some(_), Some(_)) => received.map_or(Some(InputError::IncorrectFormat), |_| None),
+            _ => None
+        };
+
+        match (accounts, sent, received, date) {
+            (Some((from, to, _)), Some(sent), Some(received), Some(date)) => Some(TransferDraft {
+                from,
+                to,
+                sent,
+                received,
+                date
+            }),
+            _ => None
+        }
+    }
+
+    pub fn clear_split_inputs(&mut self) {
+        self.split_center_input = None;
+        self.split_value_input.clear();
+        self.split_center_error = None;
+        self.split_value_error = None;
+        self.split_warning = None;
+    }
+
+    //Percent is from 0 to 100, fixed amount is positive and in the currency of the expense.
+    //Split which together with the other ones is more than the expense is rejected.
+    pub fn validate_split_form(&mut self, expense: &Expense, splits: &[ExpenseSplit]) -> Option<SplitDraft> {
+        let center = self.split_center_input.as_ref().map(|center| center.get_id());
+        let share = match self.split_kind_input {
+            ShareKind::Percent => parse_rate(&self.split_value_input)
+                .filter(|percent| *percent <= Decimal::ONE_HUNDRED)
+                .map(Share::Percent),
+            ShareKind::Amount => parse_price(&self.split_value_input, expense.get_price().currency())
+                .filter(|amount| !amount.is_negative())
+                .map(Share::Amount)
+        };
+
+        self.split_center_error = center.map_or(Some(InputError::IncorrectFormat), |_| None);
+        self.split_value_error = share.map_or(Some(InputError::IncorrectFormat), |_| None);
+
+        let (Some(center), Some(share)) = (center, share) else {
+            return None
+        };
+
+        match fits(expense, splits, center, share) {
+            Ok(()) => {
+                self.split_warning = None;
+                Some(SplitDraft {
+                    expense: expense.get_id(),
+                    center,
+                    share
+                })
+            }
+            Err(err) => {
+                self.split_warning = Some(err.to_string());
+                None
+            }
+        }
+    }
+
+    pub fn validate_rule_form(&mut self) -> Option<RuleDraft> {
+        let category = self.rule_category_input.as_ref().map(|category| category.get_id());
+
+        self.rule_category_error = category.map_or(Some(InputError::IncorrectFormat), |_| None);
+
+        category.map(|category| RuleDraft {
+            category,
+            driver: self.rule_driver_input
+        })
+    }
+
+    //Zero is allowed, it excludes the center from allocation by the driver
+    pub fn validate_driver_form(&mut self) -> Option<DriverDraft> {
+        let center = self.driver_center_input.as_ref().map(|center| center.get_id());
+        let value = Decimal::from_str(&self.driver_value_input.trim().replace(',', "."))
+            .ok()
+            .filter(|value| !value.is_sign_negative());
+
+        self.driver_center_error = center.map_or(Some(InputError::IncorrectFormat), |_| None);
+        self.driver_value_error = value.map_or(Some(InputError::IncorrectFormat), |_| None);
+
+        match (center, value) {
+            (Some(center), Some(value)) => Some(DriverDraft {
+                center,
+                kind: self.driver_kind_input,
+                value
+            }),
+            _ => None
+        }
+    }
+
+    pub fn clear_job_inputs(&mut self) {
+        self.new_job_name.clear();
+        self.new_job_client.clear();
+        self.new_job_start.clear();
+        self.new_job_end.clear();
+        self.new_job_status = JobStatus::Active;
+        self.new_job_center = None;
+        self.new_job_name_error = None;
+        self.new_job_start_error = None;
+        self.new_job_end_error = None;
+    }
+
+    //Job has no end when the end input is empty, otherwise the end can't be before the start
+    pub fn validate_job_form(&mut self) -> Option<JobDraft> {
+        let name = self.new_job_name.trim().to_string();
+        let start = parse_date(&self.new_job_start);
+        let end = match self.new_job_end.trim() {
+            "" => Some(None),
+            input => parse_date(input).map(Some)
+        };
+
+        self.new_job_name_error = if name.is_empty() {Some(InputError::IncorrectFormat)} else {None};
+        self.new_job_start_error = start.map_or(Some(InputError::IncorrectFormat), |_| None);
+        self.new_job_end_error = match (start, end) {
+            (_, None) => Some(InputError::IncorrectFormat),
+            (Some(start), Some(Some(end))) if end < start => Some(InputError::IncorrectFormat),
+            _ => None
+        };
+
+        match (name.is_empty(), start, end, self.new_job_end_error.as_ref()) {
+            (false, Some(start), Some(end), None) => Some(JobDraft {
+                name,
+                client: self.new_job_client.trim().to_string(),
+                start,
+                end,
+                status: self.new_job_status,
+                center: self.new_job_center.as_ref().map(|center| center.get_id())
+            }),
+            _ => None
+        }
+    }
+
+    pub fn clear_item_inputs(&mut self) {
+        self.new_item_name.clear();
+        self.new_item_unit.clear();
+        self.new_item_name_error = None;
+        self.new_item_unit_error = None;
+    }
+
+    pub fn validate_item_form(&mut self) -> Option<ItemDraft> {
+        let name = self.new_item_name.trim().to_string();
+        let unit = self.new_item_unit.trim().to_string();
+
+        self.new_item_name_error = if name.is_empty() {Some(InputError::IncorrectFormat)} else {None};
+        self.new_item_unit_error = if unit.is_empty() {Some(InputError::IncorrectFormat)} else {None};
+
+        match (name.is_empty(), unit.is_empty()) {
+            (false, false) => Some(ItemDraft {
+                name,
+                unit,
+                method: self.new_item_method,
+                currency: self.new_item_currency
+            }),
+            _ => None
+        }
+    }
+
+    pub fn clear_lot_inputs(&mut self) {
+        self.lot_date_input.clear();
+        self.lot_quantity_input.clear();
+        self.lot_cost_input.clear();
+        self.lot_date_error = None;
+        self.lot_quantity_error = None;
+        self.lot_cost_error = None;
+    }
+
+    //Quantity is positive, unit cost may be zero for goods received for free
+    pub fn validate_lot_form(&mut self, item: &Item) -> Option<LotDraft> {
+        let date = parse_date(&self.lot_date_input);
+        let quantity = parse_quantity(&self.lot_quantity_input);
+        let unit_cost = Money::parse(&self.lot_cost_input, item.get_currency(), Locale::default())
+            .ok()
+            .filter(|unit_cost| !unit_cost.is_negative() && fits_amount(unit_cost));
+
+        self.lot_date_error = date.map_or(Some(InputError::IncorrectFormat), |_| None);
+        self.lot_quantity_error = quantity.map_or(Some(InputError::IncorrectFormat), |_| None);
+        self.lot_cost_error = unit_cost.map_or(Some(InputError::IncorrectFormat), |_| None);
+
+        match (date, quantity, unit_cost) {
+            (Some(date), Some(quantity), Some(unit_cost)) => Some(LotDraft {
+                item: item.get_id(),
+                date,
+                quantity,
+                unit_cost
+            }),
+            _ => None
+        }
+    }
+
+    pub fn clear_issue_inputs(&mut self) {
+        self.issue_date_input.clear();
+        self.issue_quantity_input.clear();
+        self.issue_date_error = None;
+        self.issue_quantity_error = None;
+        self.inventory_warning = None;
+    }
+
+    //Issue which leaves the stock negative on any date is rejected
+    pub fn validate_issue_form(&mut self, item: &Item, lots: &[Lot], issues: &[Issue]) -> Option<IssueDraft> {
+        let date = parse_date(&self.issue_date_input);
+        let quantity = parse_quantity(&self.issue_quantity_input);
+
+        self.issue_date_error = date.map_or(Some(InputError::IncorrectFormat), |_| None);
+        self.issue_quantity_error = quantity.map_or(Some(InputError::IncorrectFormat), |_| None);
+
+        let (Some(date), Some(quantity)) = (date, quantity) else {
+            return None
+        };
+
+        match issue_fits(item.get_id(), lots, issues, date, quantity) {
+            Ok(()) => {
+                self.inventory_warning = None;
+                Some(IssueDraft {
+                    item: item.get_id(),
+                    date,
+                    quantity
+                })
+            }
+            Err(err) => {
+                self.inventory_warning = Some(err.to_string());
+                None
+            }
+        }
+    }
+
+    pub fn clear_standard_inputs(&mut self) {
+        self.standard_item_input = None;
+        self.standard_category_input = None;
+        self.standard_quantity_input.clear();
+        self.standard_price_input.clear();
+        self.standard_object_error = None;
+        self.standard_quantity_error = None;
+        self.standard_price_error = None;
+    }
+
+    //Quantity is per month, for a category it's the number of expenses
+    pub fn validate_standard_form(&mut self) -> Option<StandardDraft> {
+        let object = match self.standard_kind_input {
+            CostObjectKind::Item => self.standard_item_input.as_ref().map(|item| CostObject::Item(item.get_id())),
+            CostObjectKind::Category => self.standard_category_input.as_ref().map(|category| CostObject::Category(category.get_id()))
+        };
+        let quantity = parse_quantity(&self.standard_quantity_input);
+        let price = parse_price(&self.standard_price_input, self.standard_currency_input).filter(|price| !price.is_negative());
+
+        self.standard_object_error = object.map_or(Some(InputError::IncorrectFormat), |_| None);
+        self.standard_quantity_error = quantity.map_or(Some(InputError::IncorrectFormat), |_| None);
+        self.standard_price_error = price.map_or(Some(InputError::IncorrectFormat), |_| None);
+
+        match (object, quantity, price) {
+            (Some(object), Some(quantity), Some(price)) => Some(StandardDraft {object, quantity, price}),
+            _ => None
+        }
+    }
+
+    pub fn clear_asset_inputs(&mut self) {
+        self.asset_name_input.clear();
+        self.asset_cost_input.clear();
+        self.asset_salvage_input.clear();
+        self.asset_acquired_input.clear();
+        self.asset_life_input.clear();
+        self.asset_units_input.clear();
+        self.asset_category_input = None;
+        self.asset_name_error = None;
+        self.asset_cost_error = None;
+        self.asset_salvage_error = None;
+        self.asset_acquired_error = None;
+        self.asset_life_error = None;
+        self.asset_units_error = None;
+        self.asset_category_error = None;
+    }
+
+    //Salvage value is less than the cost, so there is always something to depreciate
+    pub fn validate_asset_form(&mut self) -> Option<AssetDraft> {
+        let name = self.asset_name_input.trim().to_string();
+        let currency = self.asset_currency_input;
+        let cost = parse_price(&self.asset_cost_input, currency).filter(|cost| !cost.is_negative());
+        let salvage = match self.asset_salvage_input.trim() {
+            "" => Some(Money::zero(currency)),
+            input => Money::parse(input, currency, Locale::default())
+                .ok()
+                .filter(|salvage| !salvage.is_negative() && cost.is_some_and(|cost| salvage.minor() < cost.minor()))
+        };
+        let acquired = parse_date(&self.asset_acquired_input);
+        let life = self.asset_life_input.trim().parse::<u32>().ok().filter(|life| *life > 0);
+        let units = match self.asset_method_input {
+            DepreciationMethod::UnitsOfProduction => parse_quantity(&self.asset_units_input).map(Some),
+            _ => Some(None)
+        };
+        let category = self.asset_category_input.as_ref().map(|category| category.get_id());
+
+        self.asset_name_error = if name.is_empty() {Some(InputError::IncorrectFormat)} else {None};
+        self.asset_cost_error = cost.map_or(Some(InputError::IncorrectFormat), |_| None);
+        self.asset_salvage_error = salvage.map_or(Some(InputError::IncorrectFormat), |_| None);
+        self.asset_acquired_error = acquired.map_or(Some(InputError::IncorrectFormat), |_| None);
+        self.asset_life_error = life.map_or(Some(InputError::IncorrectFormat), |_| None);
+        self.asset_units_error = units.map_or(Some(InputError::IncorrectFormat), |_| None);
+        self.asset_category_error = category.map_or(Some(InputError::IncorrectFormat), |_| None);
+
+        match (name.is_empty(), cost, salvage, acquired, life, units, category) {
+            (false, Some(cost), Some(salvage), Some(acquired), Some(life), Some(units), Some(category)) => Some(AssetDraft {
+                name,
+                cost,
+                salvage,
+                acquired,
+                life,
+                method: self.asset_method_input,
+                units,
+                category
+            }),
+            _ => None
+        }
+    }
+
+    pub fn clear_usage_inputs(&mut self) {
+        self.usage_month_input.clear();
+        self.usage_units_input.clear();
+        self.usage_month_error = None;
+        self.usage_units_error = None;
+        self.usage_warning = None;
+    }
+
+    //Usage of a month which depreciation is already posted can't be changed anymore
+    pub fn validate_usage_form(&mut self, asset_id: i32, expenses: &[Expense]) -> Option<(Date, Decimal)> {
+        let month = parse_month(&self.usage_month_input);
+        let units = parse_quantity(&self.usage_units_input);
+
+        self.usage_month_error = month.map_or(Some(InputError::IncorrectFormat), |_| None);
+        self.usage_units_error = units.map_or(Some(InputError::IncorrectFormat), |_| None);
+
+        let (Some(month), Some(units)) = (month, units) else {
+            return None
+        };
+
+        let posted = expenses
+            .iter()
+            .any(|expense| expense.get_asset() == Some(asset_id) && expense.get_date().year() == month.year() && expense.get_date().month() == month.month());
+
+        match posted {
+            true => {
+                self.usage_warning = Some(String::from("Амортизация за этот месяц уже начислена"));
+                None
+            }
+            false => {
+                self.usage_warning = None;
+                Some((month, units))
+            }
         }
     }
 
+    pub fn clear_category_inputs(&mut self) {
+        self.new_category_name.clear();
+        self.new_category_icon.clear();
+        self.new_category_parent = None;
+        self.new_category_name_error = None;
+        self.new_category_color_error = None;
+    }
+
+    pub fn validate_category_form(&mut self) -> Option<CategoryDraft> {
+        let name = self.new_category_name.trim().to_string();
+        let color = self.new_category_color.trim().to_uppercase();
+
+        self.new_category_name_error = if name.is_empty() {Some(InputError::IncorrectFormat)} else {None};
+        self.new_category_color_error = parse_color(&color).map_or(Some(InputError::IncorrectFormat), |_| None);
+
+        match (self.new_category_name_error.as_ref(), self.new_category_color_error.as_ref()) {
+            (None, None) => Some(CategoryDraft {
+                name,
+                kind: self.new_category_kind,
+                color,
+                icon: self.new_category_icon.trim().to_string(),
+                parent: self.new_category_parent.as_ref().map(|parent| parent.get_id())
+            }),
+            _ => None
+        }
+    }
+}
+
+pub struct CategoryDraft {
+    pub name: String,
+    pub kind: CategoryKind,
+    pub color: String,
+    pub icon: String,
+    pub parent: Option<i32>
+}
+
+pub struct NoteDraft {
+    pub name: String,
+    pub price: Money,
+    pub category: i32,
+    pub account: Option<i32>,
+    pub job: Option<i32>,
+    pub date: Date
+}
+
+//Zero is not a note, negative amounts are allowed for adjustments and refunds
+pub fn parse_price(input: &str, currency: Currency) -> Option<Money> {
+    match Money::parse(input, currency, Locale::default()) {
+        Ok(price) if !price.is_zero() && fits_amount(&price) => Some(price),
+        _ => None
+    }
+}
+
+//Amounts are stored as DECIMAL(15, 2), so the database would refuse bigger ones
+fn fits_amount(amount: &Money) -> bool {
+    amount.minor().unsigned_abs() < 1_000_000_000_000_000
+}
+
+pub fn parse_rate(input: &str) -> Option<Decimal> {
+    match Decimal::from_str(&input.trim().replace(',', ".")) {
+        Ok(rate) if rate > Decimal::ZERO => Some(rate),
+        _ => None
+    }
+}
+
+//Quantities are stored as DECIMAL(15, 3), so the database would round extra digits away
+pub fn parse_quantity(input: &str) -> Option<Decimal> {
+    parse_rate(input)
+        .map(|quantity| quantity.normalize())
+        .filter(|quantity| quantity.scale() <= 3 && *quantity < Decimal::from(1_000_000_000_000_i64))
+}
+
+//Expects date in format dd.mm.yyyy
+pub fn parse_date(input: &str) -> Option<Date> {
+    let mut parts = input.trim().split('.');
+
+    let day = parts.next()?.parse::<u8>().ok()?;
+    let month = Month::try_from(parts.next()?.parse::<u8>().ok()?).ok()?;
+    let year = parts.next()?.parse::<i32>().ok()?;
+
+    if parts.next().is_some() {
+        return None
+    }
+
+    Date::from_calendar_date(year, month, day).ok()
+}
+
+//Expects month in format mm.yyyy, returns its first day
+pub fn parse_month(input: &str) -> Option<Date> {
+    let (month, year) = input.trim().split_once('.')?;
+    let month = Month::try_from(month.parse::<u8>().ok()?).ok()?;
+
+    Date::from_calendar_date(year.parse::<i32>().ok()?, month, 1).ok()
 }
 
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub enum NotesCategory {
+    Dashboard,
     MyProfile,
     IncomesState,
     ExpensesState,
+    Categories,
+    ExchangeRates,
+    Budgets,
+    Recurring,
+    Accounts,
+    Transfers,
+    TrialBalance,
+    CostCenters,
+    CostCenterReport,
+    Overhead,
+    Jobs,
+    Inventory,
+    Standards,
+    BreakEven,
+    FixedAssets,
+    Forecast,
     //future categories...
 }
 
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TableColumn {
+    Name,
+    Price,
+    Category,
+    Date
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TableSort {
+    pub column: TableColumn,
+    pub ascending: bool
+}
+
+impl Default for TableSort {
+    fn default() -> Self {
+        TableSort {
+            column: TableColumn::Date,
+            ascending: false
+        }
+    }
+}
+
+impl TableSort {
+    //Clicking the same column again flips the direction, a new column starts ascending
+    pub fn toggle(&mut self, column: TableColumn) {
+        if self.column == column {
+            self.ascending = !self.ascending
+        } else {
+            self.column = column;
+            self.ascending = true
+        }
+    }
+
+    //Prices are compared by price_key, so amounts in different currencies can be brought to one
+    pub fn sort_incomes<'a>(&self, incomes: &'a [Income], category_name: impl Fn(i32) -> String, price_key: impl Fn(&Income) -> i64) -> Vec<&'a Income> {
+        let mut sorted: Vec<&Income> = incomes.iter().collect();
+
+        sorted.sort_by(|a, b| {
+            let ordering = match self.column {
+                TableColumn::Name => a.get_name().to_lowercase().cmp(&b.get_name().to_lowercase()),
+                TableColumn::Price => price_key(a).cmp(&price_key(b)),
+                TableColumn::Category => category_name(a.get_category()).cmp(&category_name(b.get_category())),
+                TableColumn::Date => a.get_date().cmp(&b.get_date())
+            };
+
+            if self.ascending {ordering} else {ordering.reverse()}
+        });
+
+        sorted
+    }
+}
+
 pub enum InputError {
     WrongPassword,
     IncorrectFormat
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn price_fits_decimal_15_2() {
+        assert_eq!(parse_price("9 999 999 999 999,99", Currency::RUB), Some(Money::from_minor(999_999_999_999_999, Currency::RUB)));
+        assert_eq!(parse_price("-9 999 999 999 999,99", Currency::RUB), Some(Money::from_minor(-999_999_999_999_999, Currency::RUB)));
+
+        for input in ["10 000 000 000 000", "-10000000000000", "90000000000000000", "0", "abc"] {
+            assert_eq!(parse_price(input, Currency::RUB), None, "{input}");
+        }
+    }
+
+    #[test]
+    fn quantity_fits_decimal_15_3() {
+        assert_eq!(parse_quantity("1,5"), Some(Decimal::new(15, 1)));
+        assert_eq!(parse_quantity("0.001"), Some(Decimal::new(1, 3)));
+        assert_eq!(parse_quantity("2.5000"), Some(Decimal::new(25, 1)));
+        assert_eq!(parse_quantity("999999999999.999"), Some(Decimal::new(999_999_999_999_999, 3)));
+
+        for input in ["0.0004", "1.2345", "1000000000000", "0", "-1", "abc"] {
+            assert_eq!(parse_quantity(input), None, "{input}");
+        }
+    }
+}
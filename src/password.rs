@@ -0,0 +1,69 @@
+use argon2::{Argon2, PasswordHash, PasswordHasher, PasswordVerifier};
+use argon2::password_hash::rand_core::OsRng;
+use argon2::password_hash::SaltString;
+
+//Every hash produced by Argon2 in PHC format starts with this prefix, older rows keep passwords as plain text
+const ARGON2_PREFIX: &str = "$argon2";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PasswordCheck {
+    Valid,
+    //Password is correct, but it is still stored as plain text and has to be re-hashed
+    ValidLegacy,
+    Invalid
+}
+
+pub fn hash_password(password: &str) -> Result<String, argon2::password_hash::Error> {
+    let salt = SaltString::generate(&mut OsRng);
+
+    Ok(Argon2::default().hash_password(password.as_bytes(), &salt)?.to_string())
+}
+
+pub fn verify_password(password: &str, stored: &str) -> PasswordCheck {
+    if !stored.starts_with(ARGON2_PREFIX) {
+        return if password == stored {PasswordCheck::ValidLegacy} else {PasswordCheck::Invalid}
+    }
+
+    match PasswordHash::new(stored) {
+        Ok(hash) => match Argon2::default().verify_password(password.as_bytes(), &hash) {
+            Ok(()) => PasswordCheck::Valid,
+            Err(_) => PasswordCheck::Invalid
+        },
+        Err(_) => PasswordCheck::Invalid
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hashed_password_is_verified() {
+        let stored = hash_password("Пароль-1'--").unwrap();
+
+        assert!(stored.starts_with(ARGON2_PREFIX));
+        assert_eq!(verify_password("Пароль-1'--", &stored), PasswordCheck::Valid);
+        assert_eq!(verify_password("Пароль-1", &stored), PasswordCheck::Invalid);
+        assert_eq!(verify_password("", &stored), PasswordCheck::Invalid);
+    }
+
+    #[test]
+    fn same_password_gets_different_salts() {
+        assert_ne!(hash_password("secret").unwrap(), hash_password("secret").unwrap());
+    }
+
+    #[test]
+    fn legacy_password_is_valid_only_when_it_matches() {
+        assert_eq!(verify_password("secret", "secret"), PasswordCheck::ValidLegacy);
+        assert_eq!(verify_password("Secret", "secret"), PasswordCheck::Invalid);
+        assert_eq!(verify_password("", "secret"), PasswordCheck::Invalid);
+    }
+
+    #[test]
+    fn malformed_hash_is_invalid() {
+        for stored in ["$argon2", "$argon2id$v=19$m=19456,t=2,p=1$", "$argon2id$v=19$m=x$salt$hash", "$argon2id$ünïcode"] {
+            assert_eq!(verify_password("secret", stored), PasswordCheck::Invalid, "{stored}");
+            assert_eq!(verify_password(stored, stored), PasswordCheck::Invalid, "{stored}");
+        }
+    }
+}
@@ -0,0 +1,301 @@
+use std::fmt::{Display, Formatter};
+use once_cell::sync::OnceCell;
+use sqlx::FromRow;
+use time::{Date, Duration, Month, OffsetDateTime, UtcOffset};
+use crate::category::CategoryKind;
+use crate::money::Money;
+
+//How far ahead the upcoming occurrences are shown
+pub const UPCOMING_DAYS: i64 = 30;
+
+static LOCAL_OFFSET: OnceCell<UtcOffset> = OnceCell::new();
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Period {
+    Daily,
+    Weekly,
+    Monthly,
+    Yearly
+}
+
+impl Period {
+    pub const ALL: [Period; 4] = [Period::Daily, Period::Weekly, Period::Monthly, Period::Yearly];
+
+    //Value stored in TEMPLATE_PERIOD column
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Period::Daily => "DAILY",
+            Period::Weekly => "WEEKLY",
+            Period::Monthly => "MONTHLY",
+            Period::Yearly => "YEARLY"
+        }
+    }
+}
+
+impl TryFrom<String> for Period {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        match value.as_str() {
+            "DAILY" => Ok(Period::Daily),
+            "WEEKLY" => Ok(Period::Weekly),
+            "MONTHLY" => Ok(Period::Monthly),
+            "YEARLY" => Ok(Period::Yearly),
+            other => Err(format!("unknown period: {other}"))
+        }
+    }
+}
+
+impl Display for Period {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Period::Daily => write!(f, "Каждый день"),
+            Period::Weekly => write!(f, "Каждую неделю"),
+            Period::Monthly => write!(f, "Каждый месяц"),
+            Period::Yearly => write!(f, "Каждый год")
+        }
+    }
+}
+
+#[derive(Clone, Debug, FromRow)]
+pub struct Template {
+    template_id: i32,
+    #[sqlx(try_from = "String")]
+    template_kind: CategoryKind,
+    template_name: String,
+    //read from `price` and `currency` columns
+    #[sqlx(flatten)]
+    template_price: Money,
+    template_category: i32,
+    #[sqlx(try_from = "String")]
+    template_period: Period,
+    template_start: Date,
+    template_end: Option<Date>,
+    template_generated_until: Option<Date>
+}
+
+impl Template {
+    //Templates are saved and loaded only through the database
+    #[cfg(test)]
+    pub fn new(template_id: i32, draft: TemplateDraft) -> Self {
+        Self {
+            template_id,
+            template_kind: draft.kind,
+            template_name: draft.name,
+            template_price: draft.price,
+            template_category: draft.category,
+            template_period: draft.period,
+            template_start: draft.start,
+            template_end: draft.end,
+            template_generated_until: None
+        }
+    }
+
+    #[cfg(test)]
+    pub fn with_generated_until(mut self, date: Date) -> Self {
+        self.template_generated_until = Some(date);
+        self
+    }
+
+    pub fn get_id(&self) -> i32 {
+        self.template_id
+    }
+
+    pub fn get_kind(&self) -> CategoryKind {
+        self.template_kind
+    }
+
+    pub fn get_name(&self) -> &str {
+        &self.template_name
+    }
+
+    pub fn get_price(&self) -> Money {
+        self.template_price
+    }
+
+    pub fn get_category(&self) -> i32 {
+        self.template_category
+    }
+
+    pub fn get_period(&self) -> Period {
+        self.template_period
+    }
+
+    pub fn get_start(&self) -> Date {
+        self.template_start
+    }
+
+    pub fn get_end(&self) -> Option<Date> {
+        self.template_end
+    }
+
+    //Occurrences in the range from..=to, limited by start and end of the template
+    pub fn occurrences_between(&self, from: Date, to: Date) -> Vec<Date> {
+        let to = match self.template_end {
+            Some(end) => to.min(end),
+            None => to
+        };
+
+        (0..)
+            .map_while(|index| occurrence(self.template_start, self.template_period, index))
+            .take_while(|date| *date <= to)
+            .filter(|date| *date >= from)
+            .collect()
+    }
+
+    //Occurrences which should already exist as incomes or expenses, but were not created yet
+    pub fn due_occurrences(&self, today: Date) -> Vec<Date> {
+        let from = match self.template_generated_until.and_then(|date| date.next_day()) {
+            Some(date) => date,
+            None => self.template_start
+        };
+
+        self.occurrences_between(from, today)
+    }
+}
+
+pub struct TemplateDraft {
+    pub kind: CategoryKind,
+    pub name: String,
+    pub price: Money,
+    pub category: i32,
+    pub period: Period,
+    pub start: Date,
+    pub end: Option<Date>
+}
+
+//Date of the occurrence with the index counting from the start.
+//Monthly and yearly rules keep the day of the start and use the last day of shorter months (31.01 -> 29.02 -> 31.03).
+pub fn occurrence(start: Date, period: Period, index: i64) -> Option<Date> {
+    match period {
+        Period::Daily => start.checked_add(Duration::days(index)),
+        Period::Weekly => start.checked_add(Duration::weeks(index)),
+        Period::Monthly => {
+            let months = start.year() as i64 * 12 + u8::from(start.month()) as i64 - 1 + index;
+            clamped_date(i32::try_from(months.div_euclid(12)).ok()?, months.rem_euclid(12) as u8 + 1, start.day())
+        }
+        Period::Yearly => clamped_date(i32::try_from(start.year() as i64 + index).ok()?, u8::from(start.month()), start.day())
+    }
+}
+
+fn clamped_date(year: i32, month: u8, day: u8) -> Option<Date> {
+    let month = Month::try_from(month).ok()?;
+    Date::from_calendar_date(year, month, day.min(time::util::days_in_year_month(year, month))).ok()
+}
+
+//Occurrences of all templates after today within the horizon, nearest first
+pub fn upcoming(templates: &[Template], today: Date, days: i64) -> Vec<(&Template, Date)> {
+    let (Some(from), Some(to)) = (today.next_day(), today.checked_add(Duration::days(days))) else {
+        return Vec::new()
+    };
+
+    let mut upcoming = templates
+        .iter()
+        .flat_map(|template| template.occurrences_between(from, to).into_iter().map(move |date| (template, date)))
+        .collect::<Vec<(&Template, Date)>>();
+
+    upcoming.sort_by_key(|(_, date)| *date);
+    upcoming
+}
+
+//Offset of the user's time zone can be read only while the process has a single thread,
+//so it's remembered at the start of main. UTC is used when it can't be determined.
+pub fn init_local_offset() {
+    local_offset();
+}
+
+fn local_offset() -> UtcOffset {
+    *LOCAL_OFFSET.get_or_init(|| UtcOffset::current_local_offset().unwrap_or(UtcOffset::UTC))
+}
+
+//Date in the user's time zone
+pub fn today() -> Date {
+    OffsetDateTime::now_utc().to_offset(local_offset()).date()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::money::Currency;
+
+    fn date(year: i32, month: Month, day: u8) -> Date {
+        Date::from_calendar_date(year, month, day).unwrap()
+    }
+
+    fn template(template_id: i32, period: Period, start: Date, end: Option<Date>) -> Template {
+        Template::new(template_id, TemplateDraft {
+            kind: CategoryKind::Expense,
+            name: String::from("Аренда"),
+            price: Money::from_minor(100_000, Currency::RUB),
+            category: 1,
+            period,
+            start,
+            end
+        })
+    }
+
+    #[test]
+    fn monthly_occurrence_is_clamped_to_month_end() {
+        let start = date(2024, Month::January, 31);
+        let dates = (0..4).map(|index| occurrence(start, Period::Monthly, index)).collect::<Vec<Option<Date>>>();
+
+        assert_eq!(dates, [
+            Some(start),
+            Some(date(2024, Month::February, 29)),
+            Some(date(2024, Month::March, 31)),
+            Some(date(2024, Month::April, 30))
+        ]);
+        assert_eq!(occurrence(start, Period::Monthly, 12), Some(date(2025, Month::January, 31)));
+    }
+
+    #[test]
+    fn yearly_occurrence_of_leap_day() {
+        let start = date(2024, Month::February, 29);
+
+        assert_eq!(occurrence(start, Period::Yearly, 1), Some(date(2025, Month::February, 28)));
+        assert_eq!(occurrence(start, Period::Yearly, 4), Some(date(2028, Month::February, 29)));
+        assert_eq!(occurrence(start, Period::Weekly, 1), Some(date(2024, Month::March, 7)));
+    }
+
+    #[test]
+    fn due_occurrences_resume_after_generated_until() {
+        let template = template(1, Period::Weekly, date(2024, Month::March, 1), None);
+        let today = date(2024, Month::March, 25);
+
+        assert_eq!(template.due_occurrences(today).len(), 4);
+        assert_eq!(
+            template.with_generated_until(date(2024, Month::March, 8)).due_occurrences(today),
+            [date(2024, Month::March, 15), date(2024, Month::March, 22)]
+        );
+    }
+
+    #[test]
+    fn occurrences_stop_at_template_end() {
+        let template = template(1, Period::Daily, date(2024, Month::March, 1), Some(date(2024, Month::March, 3)));
+
+        assert_eq!(
+            template.occurrences_between(date(2024, Month::February, 1), date(2024, Month::March, 31)),
+            [date(2024, Month::March, 1), date(2024, Month::March, 2), date(2024, Month::March, 3)]
+        );
+        assert_eq!(template.occurrences_between(date(2024, Month::March, 4), date(2024, Month::March, 31)), []);
+    }
+
+    #[test]
+    fn upcoming_are_sorted_within_the_horizon() {
+        let templates = [
+            template(1, Period::Monthly, date(2024, Month::January, 20), None),
+            template(2, Period::Weekly, date(2024, Month::March, 1), None)
+        ];
+        let today = date(2024, Month::March, 15);
+
+        let upcoming = |days: i64| upcoming(&templates, today, days)
+            .into_iter()
+            .map(|(template, date)| (template.get_id(), date))
+            .collect::<Vec<(i32, Date)>>();
+
+        //Today's weekly occurrence is already due, the last day of the horizon is included
+        assert_eq!(upcoming(7), [(1, date(2024, Month::March, 20)), (2, date(2024, Month::March, 22))]);
+        assert_eq!(upcoming(6), [(1, date(2024, Month::March, 20))]);
+        assert_eq!(upcoming(0), []);
+    }
+}
@@ -0,0 +1,290 @@
+use std::fmt::{Display, Formatter};
+use rust_decimal::{Decimal, RoundingStrategy};
+use sqlx::{FromRow, Row};
+use sqlx::mysql::MySqlRow;
+use time::Month;
+use crate::allocation::period_bounds;
+use crate::category::Category;
+use crate::exchange::{ConversionError, ExchangeRate, convert, total_in};
+use crate::expense::Expense;
+use crate::inventory::Lot;
+use crate::money::{Currency, Money, MoneyError};
+
+//What the standard is set for
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CostObject {
+    //actual quantity and cost are taken from purchased lots of the item
+    Item(i32),
+    //every expense of the category (or its subcategory) is one unit
+    Category(i32)
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CostObjectKind {
+    Item,
+    Category
+}
+
+impl CostObjectKind {
+    pub const ALL: [CostObjectKind; 2] = [CostObjectKind::Item, CostObjectKind::Category];
+}
+
+impl Display for CostObjectKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CostObjectKind::Item => write!(f, "Товар"),
+            CostObjectKind::Category => write!(f, "Категория")
+        }
+    }
+}
+
+//Quantity and unit price planned for every month
+#[derive(Clone, Debug)]
+pub struct StandardCost {
+    standard_id: i32,
+    standard_object: CostObject,
+    standard_quantity: Decimal,
+    standard_price: Money
+}
+
+impl StandardCost {
+    //Standards are saved and loaded only through the database
+    #[cfg(test)]
+    pub fn new(standard_id: i32, draft: StandardDraft) -> Self {
+        Self {
+            standard_id,
+            standard_object: draft.object,
+            standard_quantity: draft.quantity,
+            standard_price: draft.price
+        }
+    }
+
+    pub fn get_id(&self) -> i32 {
+        self.standard_id
+    }
+
+    pub fn get_object(&self) -> CostObject {
+        self.standard_object
+    }
+
+    pub fn get_quantity(&self) -> Decimal {
+        self.standard_quantity
+    }
+
+    pub fn get_price(&self) -> Money {
+        self.standard_price
+    }
+}
+
+//Queries alias the object to `item` or `category`, only one of them is set
+impl<'r> FromRow<'r, MySqlRow> for StandardCost {
+    fn from_row(row: &'r MySqlRow) -> Result<Self, sqlx::Error> {
+        let item: Option<i32> = row.try_get("item")?;
+        let category: Option<i32> = row.try_get("category")?;
+
+        let standard_object = match (item, category) {
+            (Some(item), None) => CostObject::Item(item),
+            (None, Some(category)) => CostObject::Category(category),
+            _ => return Err(sqlx::Error::Decode("standard must be set either for an item or for a category".into()))
+        };
+
+        Ok(StandardCost {
+            standard_id: row.try_get("standard_id")?,
+            standard_object,
+            standard_quantity: row.try_get("standard_quantity")?,
+            standard_price: Money::from_columns(row, "price", "currency")?
+        })
+    }
+}
+
+pub struct StandardDraft {
+    pub object: CostObject,
+    pub quantity: Decimal,
+    pub price: Money
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum VarianceFlag {
+    //spent less than the standard allows
+    Favorable,
+    Unfavorable,
+    None
+}
+
+impl VarianceFlag {
+    pub fn of(variance: Money) -> Self {
+        match variance.minor() {
+            0 => VarianceFlag::None,
+            minor if minor < 0 => VarianceFlag::Favorable,
+            _ => VarianceFlag::Unfavorable
+        }
+    }
+}
+
+impl Display for VarianceFlag {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            VarianceFlag::Favorable => write!(f, "благоприятное"),
+            VarianceFlag::Unfavorable => write!(f, "неблагоприятное"),
+            VarianceFlag::None => write!(f, "нет")
+        }
+    }
+}
+
+//Positive variances are overspending. Price and quantity variances add up to the total one
+pub struct Variance {
+    pub actual_quantity: Decimal,
+    pub actual_cost: Money,
+    //standard quantity at standard price
+    pub standard_cost: Money,
+    //actual quantity at actual price minus actual quantity at standard price
+    pub price_variance: Money,
+    //actual quantity minus standard quantity, at standard price
+    pub quantity_variance: Money,
+    pub total_variance: Money
+}
+
+//Actuals of the month are converted to the currency of the standard with the rates of their dates
+pub fn variance(standard: &StandardCost, period: (i32, Month), lots: &[Lot], expenses: &[Expense], categories: &[Category], rates: &[ExchangeRate]) -> Result<Variance, ConversionError> {
+    let (from, to) = period_bounds(period);
+    let currency = standard.standard_price.currency();
+
+    let (actual_quantity, actual_cost) = match standard.standard_object {
+        CostObject::Item(item_id) => {
+            let lots = lots
+                .iter()
+                .filter(|lot| lot.get_item() == item_id && lot.get_date() >= from && lot.get_date() <= to)
+                .collect::<Vec<&Lot>>();
+
+            let mut cost = Money::zero(currency);
+            for lot in &lots {
+                let lot_cost = to_money(lot.get_unit_cost().to_decimal().checked_mul(lot.get_quantity()), lot.get_unit_cost().currency())?;
+                cost = cost.checked_add(convert(rates, lot_cost, currency, lot.get_date())?)?;
+            }
+
+            (lots.iter().map(|lot| lot.get_quantity()).sum::<Decimal>(), cost)
+        }
+        CostObject::Category(category_id) => {
+            let covered = |expense_category: i32| expense_category == category_id || categories
+                .iter()
+                .any(|category| category.get_id() == expense_category && category.get_parent() == Some(category_id));
+
+            let expenses = expenses
+                .iter()
+                .filter(|expense| covered(expense.get_category()) && expense.get_date() >= from && expense.get_date() <= to)
+                .collect::<Vec<&Expense>>();
+
+            //Refunds lower the cost, but aren't purchases themselves
+            let units = expenses.iter().filter(|expense| !expense.get_price().is_negative()).count();
+
+            (Decimal::from(units), total_in(rates, expenses.iter().map(|expense| (expense.get_price(), expense.get_date())), currency)?)
+        }
+    };
+
+    let price = standard.standard_price.to_decimal();
+    let standard_cost = to_money(standard.standard_quantity.checked_mul(price), currency)?;
+    let actual_at_standard = to_money(actual_quantity.checked_mul(price), currency)?;
+
+    Ok(Variance {
+        actual_quantity,
+        actual_cost,
+        standard_cost,
+        price_variance: actual_cost.checked_sub(actual_at_standard)?,
+        quantity_variance: actual_at_standard.checked_sub(standard_cost)?,
+        total_variance: actual_cost.checked_sub(standard_cost)?
+    })
+}
+
+fn to_money(amount: Option<Decimal>, currency: Currency) -> Result<Money, MoneyError> {
+    let amount = amount.ok_or(MoneyError::Overflow)?;
+    Money::from_decimal(amount.round_dp_with_strategy(2, RoundingStrategy::MidpointAwayFromZero), currency)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use time::Date;
+    use crate::category::CategoryKind;
+    use crate::inventory::LotDraft;
+    use crate::pages::notes_page::NoteDraft;
+
+    const JANUARY: (i32, Month) = (2024, Month::January);
+
+    fn date(month: Month, day: u8) -> Date {
+        Date::from_calendar_date(2024, month, day).unwrap()
+    }
+
+    fn rub(minor: i64) -> Money {
+        Money::from_minor(minor, Currency::RUB)
+    }
+
+    fn standard(object: CostObject, quantity: i64, price: Money) -> StandardCost {
+        StandardCost::new(1, StandardDraft {object, quantity: Decimal::from(quantity), price})
+    }
+
+    fn lot(id: i32, date: Date, quantity: i64, unit_cost: i64) -> Lot {
+        Lot::new(id, LotDraft {item: 1, date, quantity: Decimal::from(quantity), unit_cost: rub(unit_cost)})
+    }
+
+    fn expense(id: i32, category: i32, price: Money, date: Date) -> Expense {
+        Expense::new(id, 1, NoteDraft {name: String::new(), price, category, account: None, job: None, date})
+    }
+
+    fn category(id: i32, parent: Option<i32>) -> Category {
+        Category::new(id, None, String::new(), CategoryKind::Expense, String::new(), String::new(), parent)
+    }
+
+    #[test]
+    fn item_variances_add_up_to_the_total() {
+        //Standard is 10 units at 2.00, 12 units were bought at 2.50
+        let standard = standard(CostObject::Item(1), 10, rub(200));
+        let lots = [
+            lot(1, date(Month::January, 5), 12, 250),
+            lot(2, date(Month::February, 1), 100, 100)
+        ];
+
+        let variance = variance(&standard, JANUARY, &lots, &[], &[], &[]).unwrap();
+
+        assert_eq!(variance.actual_quantity, Decimal::from(12));
+        assert_eq!(variance.actual_cost, rub(3_000));
+        assert_eq!(variance.standard_cost, rub(2_000));
+        assert_eq!(variance.price_variance, rub(600));
+        assert_eq!(variance.quantity_variance, rub(400));
+        assert_eq!(variance.total_variance, rub(1_000));
+        assert_eq!(VarianceFlag::of(variance.total_variance), VarianceFlag::Unfavorable);
+    }
+
+    #[test]
+    fn category_counts_expenses_of_subcategories_as_units() {
+        //Standard is 3 expenses at 100.00, refund lowers the cost but isn't a unit
+        let standard = standard(CostObject::Category(5), 3, rub(10_000));
+        let categories = [category(5, None), category(6, Some(5)), category(7, None)];
+        let expenses = [
+            expense(1, 5, rub(12_000), date(Month::January, 3)),
+            expense(2, 6, rub(9_000), date(Month::January, 20)),
+            expense(3, 5, rub(-2_000), date(Month::January, 25)),
+            expense(4, 7, rub(100_000), date(Month::January, 10)),
+            expense(5, 5, rub(100_000), date(Month::February, 1))
+        ];
+
+        let variance = variance(&standard, JANUARY, &[], &expenses, &categories, &[]).unwrap();
+
+        assert_eq!(variance.actual_quantity, Decimal::from(2));
+        assert_eq!(variance.actual_cost, rub(19_000));
+        assert_eq!(variance.price_variance, rub(-1_000));
+        assert_eq!(variance.quantity_variance, rub(-10_000));
+        assert_eq!(variance.total_variance, rub(-11_000));
+        assert_eq!(VarianceFlag::of(variance.price_variance), VarianceFlag::Favorable);
+    }
+
+    #[test]
+    fn actuals_without_a_rate_fail() {
+        let standard = standard(CostObject::Category(5), 1, rub(10_000));
+        let expenses = [expense(1, 5, Money::from_minor(100, Currency::USD), date(Month::January, 3))];
+
+        assert!(matches!(
+            variance(&standard, JANUARY, &[], &expenses, &[category(5, None)], &[]),
+            Err(ConversionError::NoRate(Currency::USD, Currency::RUB, _))
+        ));
+    }
+}
@@ -57,6 +57,24 @@ impl container::StyleSheet for TestContainer {
     }
 }
 
+pub struct ColorSwatch(pub Color);
+
+impl container::StyleSheet for ColorSwatch {
+    type Style = Theme;
+
+    fn appearance(&self, _style: &Self::Style) -> container::Appearance {
+        container::Appearance {
+            background: Some(Background::Color(self.0)),
+            border: Border {
+                color: Color::BLACK,
+                width: 1.0,
+                radius: Radius::from(4)
+            },
+            ..Default::default()
+        }
+    }
+}
+
 //TEXT INPUTS
 
 pub struct ErrorTextInputStyle;
@@ -1,5 +1,18 @@
 use crate::income::Income;
 use crate::expense::Expense;
+use crate::category::Category;
+use crate::exchange::ExchangeRate;
+use crate::budget::Budget;
+use crate::recurring::Template;
+use crate::account::{Account, Transfer};
+use crate::ledger::Posting;
+use crate::cost_center::{CostCenter, ExpenseSplit};
+use crate::allocation::{Allocation, AllocationRule, DriverValue};
+use crate::job::{Job, JobStatus};
+use crate::inventory::{CostMethod, Issue, Item, Lot};
+use crate::standard::{CostObject, StandardCost};
+use crate::breakeven::{CategoryBehavior, CostBehavior};
+use crate::asset::{Asset, Usage};
 
 #[derive(Clone, Debug)]
 pub struct User {
@@ -8,7 +21,27 @@ pub struct User {
     pub first_name: Option<String>,
     pub nickname: String,
     incomes: Option<Vec<Income>>,
-    expenses: Option<Vec<Expense>>
+    expenses: Option<Vec<Expense>>,
+    categories: Option<Vec<Category>>,
+    exchange_rates: Option<Vec<ExchangeRate>>,
+    budgets: Option<Vec<Budget>>,
+    templates: Option<Vec<Template>>,
+    accounts: Option<Vec<Account>>,
+    transfers: Option<Vec<Transfer>>,
+    postings: Option<Vec<Posting>>,
+    cost_centers: Option<Vec<CostCenter>>,
+    splits: Option<Vec<ExpenseSplit>>,
+    allocation_rules: Option<Vec<AllocationRule>>,
+    drivers: Option<Vec<DriverValue>>,
+    allocations: Option<Vec<Allocation>>,
+    jobs: Option<Vec<Job>>,
+    items: Option<Vec<Item>>,
+    lots: Option<Vec<Lot>>,
+    issues: Option<Vec<Issue>>,
+    standards: Option<Vec<StandardCost>>,
+    behaviors: Option<Vec<CategoryBehavior>>,
+    assets: Option<Vec<Asset>>,
+    usage: Option<Vec<Usage>>
 }
 
 impl User {
@@ -19,7 +52,27 @@ impl User {
             first_name,
             nickname,
             incomes: None,
-            expenses: None
+            expenses: None,
+            categories: None,
+            exchange_rates: None,
+            budgets: None,
+            templates: None,
+            accounts: None,
+            transfers: None,
+            postings: None,
+            cost_centers: None,
+            splits: None,
+            allocation_rules: None,
+            drivers: None,
+            allocations: None,
+            jobs: None,
+            items: None,
+            lots: None,
+            issues: None,
+            standards: None,
+            behaviors: None,
+            assets: None,
+            usage: None
         }
     }
 
@@ -27,6 +80,439 @@ impl User {
         *&self.id
     }
 
+    pub fn get_incomes(&self) -> Option<&[Income]> {
+        self.incomes.as_deref()
+    }
+
+    pub fn get_expenses(&self) -> Option<&[Expense]> {
+        self.expenses.as_deref()
+    }
+
+    pub fn get_categories(&self) -> Option<&[Category]> {
+        self.categories.as_deref()
+    }
+
+    pub fn get_category(&self, category_id: i32) -> Option<&Category> {
+        self.categories.iter().flatten().find(|category| category.get_id() == category_id)
+    }
+
+    //Name for tables, falls back to the id while categories are not loaded
+    pub fn category_name(&self, category_id: i32) -> String {
+        match self.get_category(category_id) {
+            Some(category) => category.to_string(),
+            None => format!("#{category_id}")
+        }
+    }
+
+    pub fn add_categories_to_user(&mut self, categories: Vec<Category>) {
+        self.categories = Some(categories)
+    }
+
+    pub fn push_category(&mut self, category: Category) {
+        self.categories.get_or_insert_with(Vec::new).push(category)
+    }
+
+    //Subcategories are removed too, database deletes them by cascade
+    //Budgets, allocation rules, allocations, standards and classifications of removed categories are deleted by the database too
+    pub fn remove_category(&mut self, category_id: i32) {
+        let removed = self.categories
+            .iter()
+            .flatten()
+            .filter(|category| category.get_id() == category_id || category.get_parent() == Some(category_id))
+            .map(|category| category.get_id())
+            .collect::<Vec<i32>>();
+
+        if let Some(categories) = self.categories.as_mut() {
+            categories.retain(|category| !removed.contains(&category.get_id()))
+        }
+
+        if let Some(budgets) = self.budgets.as_mut() {
+            budgets.retain(|budget| !removed.contains(&budget.get_category()))
+        }
+
+        if let Some(allocation_rules) = self.allocation_rules.as_mut() {
+            allocation_rules.retain(|rule| !removed.contains(&rule.get_category()))
+        }
+
+        if let Some(allocations) = self.allocations.as_mut() {
+            allocations.retain(|allocation| !removed.contains(&allocation.get_category()))
+        }
+
+        if let Some(standards) = self.standards.as_mut() {
+            standards.retain(|standard| !matches!(standard.get_object(), CostObject::Category(category) if removed.contains(&category)))
+        }
+
+        if let Some(behaviors) = self.behaviors.as_mut() {
+            behaviors.retain(|behavior| !removed.contains(&behavior.get_category()))
+        }
+    }
+
+    //Totals are calculated with whatever rates are already loaded
+    pub fn get_exchange_rates(&self) -> &[ExchangeRate] {
+        self.exchange_rates.as_deref().unwrap_or(&[])
+    }
+
+    pub fn add_exchange_rates_to_user(&mut self, exchange_rates: Vec<ExchangeRate>) {
+        self.exchange_rates = Some(exchange_rates)
+    }
+
+    pub fn push_exchange_rate(&mut self, exchange_rate: ExchangeRate) {
+        self.exchange_rates.get_or_insert_with(Vec::new).push(exchange_rate)
+    }
+
+    pub fn remove_exchange_rate(&mut self, rate_id: i32) {
+        if let Some(exchange_rates) = self.exchange_rates.as_mut() {
+            exchange_rates.retain(|exchange_rate| exchange_rate.get_id() != rate_id)
+        }
+    }
+
+    pub fn get_budgets(&self) -> Option<&[Budget]> {
+        self.budgets.as_deref()
+    }
+
+    pub fn add_budgets_to_user(&mut self, budgets: Vec<Budget>) {
+        self.budgets = Some(budgets)
+    }
+
+    pub fn remove_budget(&mut self, budget_id: i32) {
+        if let Some(budgets) = self.budgets.as_mut() {
+            budgets.retain(|budget| budget.get_id() != budget_id)
+        }
+    }
+
+    pub fn get_templates(&self) -> Option<&[Template]> {
+        self.templates.as_deref()
+    }
+
+    pub fn add_templates_to_user(&mut self, templates: Vec<Template>) {
+        self.templates = Some(templates)
+    }
+
+    pub fn remove_template(&mut self, template_id: i32) {
+        if let Some(templates) = self.templates.as_mut() {
+            templates.retain(|template| template.get_id() != template_id)
+        }
+    }
+
+    pub fn get_accounts(&self) -> Option<&[Account]> {
+        self.accounts.as_deref()
+    }
+
+    pub fn get_account(&self, account_id: i32) -> Option<&Account> {
+        self.accounts.iter().flatten().find(|account| account.get_id() == account_id)
+    }
+
+    pub fn account_name(&self, account_id: Option<i32>) -> String {
+        match account_id.and_then(|account_id| self.get_account(account_id)) {
+            Some(account) => account.get_name().to_string(),
+            None => String::from("—")
+        }
+    }
+
+    pub fn add_accounts_to_user(&mut self, accounts: Vec<Account>) {
+        self.accounts = Some(accounts)
+    }
+
+    pub fn push_account(&mut self, account: Account) {
+        self.accounts.get_or_insert_with(Vec::new).push(account)
+    }
+
+    pub fn get_transfers(&self) -> &[Transfer] {
+        self.transfers.as_deref().unwrap_or(&[])
+    }
+
+    pub fn add_transfers_to_user(&mut self, transfers: Vec<Transfer>) {
+        self.transfers = Some(transfers)
+    }
+
+    pub fn push_transfer(&mut self, transfer: Transfer) {
+        self.transfers.get_or_insert_with(Vec::new).push(transfer)
+    }
+
+    pub fn remove_transfer(&mut self, transfer_id: i32) {
+        if let Some(transfers) = self.transfers.as_mut() {
+            transfers.retain(|transfer| transfer.get_id() != transfer_id)
+        }
+    }
+
+    pub fn get_postings(&self) -> Option<&[Posting]> {
+        self.postings.as_deref()
+    }
+
+    pub fn add_postings_to_user(&mut self, postings: Vec<Posting>) {
+        self.postings = Some(postings)
+    }
+
+    pub fn clear_postings(&mut self) {
+        self.postings = None
+    }
+
+    pub fn get_cost_centers(&self) -> Option<&[CostCenter]> {
+        self.cost_centers.as_deref()
+    }
+
+    pub fn get_cost_center(&self, center_id: i32) -> Option<&CostCenter> {
+        self.cost_centers.iter().flatten().find(|center| center.get_id() == center_id)
+    }
+
+    pub fn cost_center_name(&self, center_id: Option<i32>) -> String {
+        match center_id.and_then(|center_id| self.get_cost_center(center_id)) {
+            Some(center) => center.get_name().to_string(),
+            None => String::from("Не разнесено")
+        }
+    }
+
+    pub fn add_cost_centers_to_user(&mut self, cost_centers: Vec<CostCenter>) {
+        self.cost_centers = Some(cost_centers)
+    }
+
+    pub fn push_cost_center(&mut self, cost_center: CostCenter) {
+        self.cost_centers.get_or_insert_with(Vec::new).push(cost_center)
+    }
+
+    //Splits, drivers and allocations of the center are deleted by the database too
+    pub fn remove_cost_center(&mut self, center_id: i32) {
+        if let Some(cost_centers) = self.cost_centers.as_mut() {
+            cost_centers.retain(|center| center.get_id() != center_id)
+        }
+
+        if let Some(splits) = self.splits.as_mut() {
+            splits.retain(|split| split.get_center() != center_id)
+        }
+
+        if let Some(drivers) = self.drivers.as_mut() {
+            drivers.retain(|driver| driver.get_center() != center_id)
+        }
+
+        if let Some(allocations) = self.allocations.as_mut() {
+            allocations.retain(|allocation| allocation.get_center() != center_id)
+        }
+    }
+
+    pub fn get_splits(&self) -> &[ExpenseSplit] {
+        self.splits.as_deref().unwrap_or(&[])
+    }
+
+    pub fn add_splits_to_user(&mut self, splits: Vec<ExpenseSplit>) {
+        self.splits = Some(splits)
+    }
+
+    pub fn remove_split(&mut self, split_id: i32) {
+        if let Some(splits) = self.splits.as_mut() {
+            splits.retain(|split| split.get_id() != split_id)
+        }
+    }
+
+    pub fn get_allocation_rules(&self) -> Option<&[AllocationRule]> {
+        self.allocation_rules.as_deref()
+    }
+
+    pub fn add_allocation_rules_to_user(&mut self, allocation_rules: Vec<AllocationRule>) {
+        self.allocation_rules = Some(allocation_rules)
+    }
+
+    pub fn remove_allocation_rule(&mut self, rule_id: i32) {
+        if let Some(allocation_rules) = self.allocation_rules.as_mut() {
+            allocation_rules.retain(|rule| rule.get_id() != rule_id)
+        }
+    }
+
+    pub fn get_drivers(&self) -> &[DriverValue] {
+        self.drivers.as_deref().unwrap_or(&[])
+    }
+
+    pub fn add_drivers_to_user(&mut self, drivers: Vec<DriverValue>) {
+        self.drivers = Some(drivers)
+    }
+
+    pub fn get_allocations(&self) -> &[Allocation] {
+        self.allocations.as_deref().unwrap_or(&[])
+    }
+
+    pub fn add_allocations_to_user(&mut self, allocations: Vec<Allocation>) {
+        self.allocations = Some(allocations)
+    }
+
+    pub fn get_jobs(&self) -> Option<&[Job]> {
+        self.jobs.as_deref()
+    }
+
+    pub fn get_job(&self, job_id: i32) -> Option<&Job> {
+        self.jobs.iter().flatten().find(|job| job.get_id() == job_id)
+    }
+
+    pub fn add_jobs_to_user(&mut self, jobs: Vec<Job>) {
+        self.jobs = Some(jobs)
+    }
+
+    pub fn push_job(&mut self, job: Job) {
+        self.jobs.get_or_insert_with(Vec::new).push(job)
+    }
+
+    pub fn set_job_status(&mut self, job_id: i32, status: JobStatus) {
+        if let Some(job) = self.jobs.iter_mut().flatten().find(|job| job.get_id() == job_id) {
+            job.set_status(status)
+        }
+    }
+
+    pub fn remove_job(&mut self, job_id: i32) {
+        if let Some(jobs) = self.jobs.as_mut() {
+            jobs.retain(|job| job.get_id() != job_id)
+        }
+    }
+
+    pub fn get_items(&self) -> Option<&[Item]> {
+        self.items.as_deref()
+    }
+
+    pub fn get_item(&self, item_id: i32) -> Option<&Item> {
+        self.items.iter().flatten().find(|item| item.get_id() == item_id)
+    }
+
+    pub fn add_items_to_user(&mut self, items: Vec<Item>) {
+        self.items = Some(items)
+    }
+
+    pub fn push_item(&mut self, item: Item) {
+        self.items.get_or_insert_with(Vec::new).push(item)
+    }
+
+    pub fn set_item_method(&mut self, item_id: i32, method: CostMethod) {
+        if let Some(item) = self.items.iter_mut().flatten().find(|item| item.get_id() == item_id) {
+            item.set_method(method)
+        }
+    }
+
+    //Lots, issues and the standard of the item are deleted by the database too
+    pub fn remove_item(&mut self, item_id: i32) {
+        if let Some(items) = self.items.as_mut() {
+            items.retain(|item| item.get_id() != item_id)
+        }
+
+        if let Some(lots) = self.lots.as_mut() {
+            lots.retain(|lot| lot.get_item() != item_id)
+        }
+
+        if let Some(issues) = self.issues.as_mut() {
+            issues.retain(|issue| issue.get_item() != item_id)
+        }
+
+        if let Some(standards) = self.standards.as_mut() {
+            standards.retain(|standard| standard.get_object() != CostObject::Item(item_id))
+        }
+    }
+
+    pub fn get_lots(&self) -> &[Lot] {
+        self.lots.as_deref().unwrap_or(&[])
+    }
+
+    pub fn add_lots_to_user(&mut self, lots: Vec<Lot>) {
+        self.lots = Some(lots)
+    }
+
+    pub fn push_lot(&mut self, lot: Lot) {
+        self.lots.get_or_insert_with(Vec::new).push(lot)
+    }
+
+    pub fn remove_lot(&mut self, lot_id: i32) {
+        if let Some(lots) = self.lots.as_mut() {
+            lots.retain(|lot| lot.get_id() != lot_id)
+        }
+    }
+
+    pub fn get_issues(&self) -> &[Issue] {
+        self.issues.as_deref().unwrap_or(&[])
+    }
+
+    pub fn add_issues_to_user(&mut self, issues: Vec<Issue>) {
+        self.issues = Some(issues)
+    }
+
+    pub fn push_issue(&mut self, issue: Issue) {
+        self.issues.get_or_insert_with(Vec::new).push(issue)
+    }
+
+    pub fn remove_issue(&mut self, issue_id: i32) {
+        if let Some(issues) = self.issues.as_mut() {
+            issues.retain(|issue| issue.get_id() != issue_id)
+        }
+    }
+
+    pub fn get_standards(&self) -> Option<&[StandardCost]> {
+        self.standards.as_deref()
+    }
+
+    pub fn add_standards_to_user(&mut self, standards: Vec<StandardCost>) {
+        self.standards = Some(standards)
+    }
+
+    pub fn remove_standard(&mut self, standard_id: i32) {
+        if let Some(standards) = self.standards.as_mut() {
+            standards.retain(|standard| standard.get_id() != standard_id)
+        }
+    }
+
+    pub fn get_behaviors(&self) -> &[CategoryBehavior] {
+        self.behaviors.as_deref().unwrap_or(&[])
+    }
+
+    pub fn add_behaviors_to_user(&mut self, behaviors: Vec<CategoryBehavior>) {
+        self.behaviors = Some(behaviors)
+    }
+
+    //Replaces the previous classification of the category
+    pub fn set_behavior(&mut self, category_id: i32, kind: CostBehavior) {
+        let behaviors = self.behaviors.get_or_insert_with(Vec::new);
+        behaviors.retain(|behavior| behavior.get_category() != category_id);
+        behaviors.push(CategoryBehavior::new(category_id, kind))
+    }
+
+    pub fn get_assets(&self) -> Option<&[Asset]> {
+        self.assets.as_deref()
+    }
+
+    pub fn get_asset(&self, asset_id: i32) -> Option<&Asset> {
+        self.assets.iter().flatten().find(|asset| asset.get_id() == asset_id)
+    }
+
+    pub fn add_assets_to_user(&mut self, assets: Vec<Asset>) {
+        self.assets = Some(assets)
+    }
+
+    pub fn push_asset(&mut self, asset: Asset) {
+        self.assets.get_or_insert_with(Vec::new).push(asset)
+    }
+
+    //Usage and depreciation expenses of the asset are deleted by the database too
+    pub fn remove_asset(&mut self, asset_id: i32) {
+        if let Some(assets) = self.assets.as_mut() {
+            assets.retain(|asset| asset.get_id() != asset_id)
+        }
+
+        if let Some(usage) = self.usage.as_mut() {
+            usage.retain(|usage| usage.get_asset() != asset_id)
+        }
+
+        if let Some(expenses) = self.expenses.as_mut() {
+            expenses.retain(|expense| expense.get_asset() != Some(asset_id))
+        }
+    }
+
+    pub fn get_usage(&self) -> &[Usage] {
+        self.usage.as_deref().unwrap_or(&[])
+    }
+
+    pub fn add_usage_to_user(&mut self, usage: Vec<Usage>) {
+        self.usage = Some(usage)
+    }
+
+    //Replaces the previous usage of the asset in the same month
+    pub fn set_usage(&mut self, usage: Usage) {
+        let all = self.usage.get_or_insert_with(Vec::new);
+        all.retain(|other| other.get_asset() != usage.get_asset() || other.get_month() != usage.get_month());
+        all.push(usage)
+    }
+
     pub fn add_incomes_to_user(&mut self, incomes: Vec<Income>) {
         *&mut self.incomes = Some(incomes)
     }
@@ -34,4 +520,41 @@ impl User {
     pub fn add_expenses_to_user(&mut self, expenses: Vec<Expense>) {
         *&mut self.expenses = Some(expenses)
     }
+
+    pub fn push_income(&mut self, income: Income) {
+        self.incomes.get_or_insert_with(Vec::new).push(income)
+    }
+
+    pub fn push_expense(&mut self, expense: Expense) {
+        self.expenses.get_or_insert_with(Vec::new).push(expense)
+    }
+
+    pub fn replace_income(&mut self, income: Income) {
+        if let Some(old) = self.incomes.iter_mut().flatten().find(|old| old.get_id() == income.get_id()) {
+            *old = income
+        }
+    }
+
+    pub fn replace_expense(&mut self, expense: Expense) {
+        if let Some(old) = self.expenses.iter_mut().flatten().find(|old| old.get_id() == expense.get_id()) {
+            *old = expense
+        }
+    }
+
+    pub fn remove_income(&mut self, income_id: i32) {
+        if let Some(incomes) = self.incomes.as_mut() {
+            incomes.retain(|income| income.get_id() != income_id)
+        }
+    }
+
+    //Splits of the expense are deleted by the database too
+    pub fn remove_expense(&mut self, expense_id: i32) {
+        if let Some(expenses) = self.expenses.as_mut() {
+            expenses.retain(|expense| expense.get_id() != expense_id)
+        }
+
+        if let Some(splits) = self.splits.as_mut() {
+            splits.retain(|split| split.get_expense() != expense_id)
+        }
+    }
 }
\ No newline at end of file